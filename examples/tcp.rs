@@ -1,5 +1,5 @@
-use asynk::net::tcp::TcpListener;
-use futures::{AsyncReadExt, AsyncWriteExt, StreamExt};
+use asynk::{io::BufStream, net::tcp::TcpListener};
+use futures::AsyncWriteExt;
 
 const RESPONSE: &str = "HTTP/1.1 200 OK
 Content-Type: text/html
@@ -15,22 +15,22 @@ fn main() {
 }
 
 async fn main_future() {
-    let addr = "127.0.0.1:8040".parse().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:8040").await.unwrap();
 
-    let listener = TcpListener::bind(addr).unwrap();
-    let mut accept = listener.accept();
+    loop {
+        let (stream, addr) = listener.accept().await.unwrap();
 
-    while let Some(res) = accept.next().await {
         asynk::spawn(async move {
-            let (mut stream, addr) = res.unwrap();
-
             println!("got connection from addr: {}", addr);
 
-            let mut buf = vec![];
-
-            stream.read_to_end(&mut buf).await.unwrap();
+            let mut stream = BufStream::new(stream);
 
-            println!("{}", String::from_utf8_lossy(&buf));
+            // Request head ends with the empty line
+            let mut line = String::new();
+            while stream.read_line(&mut line).await.unwrap() != 0 && line.trim_end() != "" {
+                print!("{}", line);
+                line.clear();
+            }
 
             stream.write_all(RESPONSE.as_bytes()).await.unwrap();
 
@@ -0,0 +1,24 @@
+use asynk::net::tcp::stream::TcpStream;
+use futures::{AsyncReadExt, AsyncWriteExt};
+
+const REQUEST: &str = "GET / HTTP/1.1
+Host: localhost
+
+";
+
+fn main() {
+    asynk::builder().build().register();
+    asynk::block_on(main_future());
+}
+
+async fn main_future() {
+    let mut stream = TcpStream::connect("localhost:8040").await.unwrap();
+
+    stream.write_all(REQUEST.as_bytes()).await.unwrap();
+    stream.flush().await.unwrap();
+
+    let mut buf = vec![0; 1024];
+    let n = stream.read(&mut buf).await.unwrap();
+
+    println!("{}", String::from_utf8_lossy(&buf[..n]));
+}
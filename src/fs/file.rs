@@ -0,0 +1,428 @@
+use super::{asyncify, Metadata, OpenOptions};
+use crate::task::spawn_blocking;
+use futures::{future::BoxFuture, AsyncRead, AsyncSeek, AsyncWrite, FutureExt};
+use parking_lot::Mutex;
+use std::{
+    cmp, fmt,
+    fs::File as StdFile,
+    future::{poll_fn, Future},
+    io::{self, Read, Seek, SeekFrom, Write},
+    mem,
+    os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd},
+    path::Path,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+/// Maximum size of the single read or write
+const MAX_BUF: usize = 2 * 1024 * 1024;
+
+/// Opened file.
+///
+/// Each read, write or seek is executed in background, so only one of them may be in progress.
+/// The data is read ahead into the internal buffer, which is discarded on write and seek.
+/// The written data is accepted at once, its write error is returned by the next operation.
+pub struct File {
+    std: Arc<StdFile>,
+    state: State,
+}
+
+enum State {
+    /// `None` only while the buffer is taken by the operation being started
+    Idle(Option<Buf>),
+    Busy(BoxFuture<'static, (Operation, Buf)>),
+}
+
+enum Operation {
+    Read(io::Result<usize>),
+    Write(io::Result<usize>),
+    Seek(io::Result<u64>),
+}
+
+/// Data read ahead or pending for write
+#[derive(Default)]
+struct Buf {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl File {
+    /// Open the file in the read-only mode
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        OpenOptions::new().read(true).open(path).await
+    }
+
+    /// Open the file in the write-only mode, creating or truncating it
+    pub async fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .await
+    }
+
+    pub fn options() -> OpenOptions {
+        OpenOptions::new()
+    }
+
+    pub fn from_std(file: StdFile) -> Self {
+        Self {
+            std: Arc::new(file),
+            state: State::Idle(Some(Buf::default())),
+        }
+    }
+
+    /// Wait for the operation in progress and turn the file into the standard library one.
+    /// The cursor is set to the position of the data read by the caller.
+    pub async fn into_std(mut self) -> StdFile {
+        // Seek errors are ignored, as the position can't be restored anyway
+        self.sync_position().await.ok();
+
+        // Only the operations kept in the state share the file, and the last one is completed
+        match Arc::try_unwrap(self.std) {
+            Ok(std) => std,
+            Err(_) => unreachable!("file is shared by the completed operation"),
+        }
+    }
+
+    /// The returned future doesn't borrow the file, so it may be sent to another task
+    pub fn metadata(&self) -> impl Future<Output = io::Result<Metadata>> + Send + 'static {
+        let std = self.detached();
+        async move { asyncify(move || std?.metadata()).await }
+    }
+
+    /// Truncate or extend the file to the given size
+    pub async fn set_len(&mut self, size: u64) -> io::Result<()> {
+        self.sync_position().await?;
+
+        let std = self.detached()?;
+        asyncify(move || std.set_len(size)).await
+    }
+
+    /// Flush the data and metadata to the disk
+    pub async fn sync_all(&mut self) -> io::Result<()> {
+        self.sync_position().await?;
+
+        let std = self.detached()?;
+        asyncify(move || std.sync_all()).await
+    }
+
+    /// Flush the data to the disk, omitting the metadata if possible
+    pub async fn sync_data(&mut self) -> io::Result<()> {
+        self.sync_position().await?;
+
+        let std = self.detached()?;
+        asyncify(move || std.sync_data()).await
+    }
+
+    /// Duplicate of the descriptor for the operations which don't use the cursor. They aren't
+    /// kept in the state, so they must not share the file, which `into_std` takes back.
+    fn detached(&self) -> io::Result<StdFile> {
+        self.std.try_clone()
+    }
+
+    /// Wait for the operation in progress and drop the data read ahead, moving the cursor back
+    async fn sync_position(&mut self) -> io::Result<()> {
+        poll_fn(|cx| self.poll_idle(cx)).await?;
+
+        let State::Idle(Some(ref buf)) = self.state else {
+            unreachable!("file is idle");
+        };
+
+        // The seek is the file's operation, so it's completed by the next one if this is cancelled
+        if !buf.is_empty() {
+            poll_fn(|cx| Pin::new(&mut *self).poll_seek(cx, SeekFrom::Current(0))).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Wait for the operation in progress, returning its write error
+    fn poll_idle(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let State::Busy(ref mut fut) = self.state else {
+            return Poll::Ready(Ok(()));
+        };
+
+        let (op, buf) = ready!(fut.poll_unpin(cx));
+        self.state = State::Idle(Some(buf));
+
+        match op {
+            Operation::Write(Err(e)) => Poll::Ready(Err(e)),
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn take_buf(&mut self) -> Buf {
+        match self.state {
+            State::Idle(ref mut buf) => buf.take().expect("file buffer is taken"),
+            State::Busy(_) => unreachable!("file is busy"),
+        }
+    }
+
+    fn start_read(&mut self, len: usize) {
+        let buf = self.take_buf();
+        let std = Arc::clone(&self.std);
+        let len = cmp::min(len, MAX_BUF);
+
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        if crate::uring::is_enabled() {
+            self.state = State::Busy(Box::pin(async move {
+                let mut data = buf.data;
+                data.clear();
+                data.reserve(len);
+
+                let (res, data) = crate::uring::read_at(&*std, data, CURRENT_POSITION).await;
+                (Operation::Read(res), Buf { data, pos: 0 })
+            }));
+            return;
+        }
+
+        self.state = State::Busy(blocking(Operation::Read, buf, move |buf| {
+            buf.data.resize(len, 0);
+            buf.pos = 0;
+
+            let res = (&*std).read(&mut buf.data);
+            buf.data.truncate(*res.as_ref().unwrap_or(&0));
+            Operation::Read(res)
+        }));
+    }
+
+    fn start_write(&mut self, src: &[u8]) -> usize {
+        let mut buf = self.take_buf();
+        let std = Arc::clone(&self.std);
+
+        let seek = buf.discard();
+        let n = cmp::min(src.len(), MAX_BUF);
+        buf.data.extend_from_slice(&src[..n]);
+
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        if crate::uring::is_enabled() && seek == 0 {
+            self.state = State::Busy(Box::pin(async move {
+                let mut data = mem::take(&mut buf.data);
+
+                let res = loop {
+                    let (res, rest) = crate::uring::write_at(&*std, data, CURRENT_POSITION).await;
+                    data = rest;
+
+                    match res {
+                        Ok(0) => break Err(io::ErrorKind::WriteZero.into()),
+                        Ok(written) if written < data.len() => drop(data.drain(..written)),
+                        Ok(_) => break Ok(n),
+                        Err(e) => break Err(e),
+                    }
+                };
+
+                data.clear();
+                buf.data = data;
+                (Operation::Write(res), buf)
+            }));
+            return n;
+        }
+
+        self.state = State::Busy(blocking(Operation::Write, buf, move |buf| {
+            let res = (|| {
+                if seek != 0 {
+                    (&*std).seek(SeekFrom::Current(seek))?;
+                }
+                (&*std).write_all(&buf.data).map(|_| n)
+            })();
+
+            buf.clear();
+            Operation::Write(res)
+        }));
+        n
+    }
+
+    fn start_seek(&mut self, pos: SeekFrom) {
+        let mut buf = self.take_buf();
+        let std = Arc::clone(&self.std);
+
+        // The cursor is ahead of the caller by the data read ahead
+        let pos = match pos {
+            SeekFrom::Current(offset) => SeekFrom::Current(offset + buf.discard()),
+            pos => {
+                buf.discard();
+                pos
+            }
+        };
+
+        self.state = State::Busy(blocking(Operation::Seek, buf, move |_| {
+            Operation::Seek((&*std).seek(pos))
+        }));
+    }
+}
+
+/// Offset making io_uring use and advance the file cursor, as the blocking operations do
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+const CURRENT_POSITION: u64 = u64::MAX;
+
+/// Run the operation on the buffer on the blocking pool. The buffer stays in the slot while
+/// the closure runs, so it's given back even if the closure panics, with its data dropped.
+fn blocking<F, T>(
+    wrap: fn(io::Result<T>) -> Operation,
+    buf: Buf,
+    f: F,
+) -> BoxFuture<'static, (Operation, Buf)>
+where
+    T: 'static,
+    F: FnOnce(&mut Buf) -> Operation + Send + 'static,
+{
+    let slot = Arc::new(Mutex::new(buf));
+
+    let handle = spawn_blocking({
+        let slot = Arc::clone(&slot);
+        move || f(&mut slot.lock())
+    });
+
+    Box::pin(async move {
+        let res = handle.await;
+        let mut buf = mem::take(&mut *slot.lock());
+
+        let op = res.unwrap_or_else(|e| {
+            // The data of the failed operation is in unknown state
+            buf.clear();
+            wrap(Err(io::Error::other(e)))
+        });
+        (op, buf)
+    })
+}
+
+impl Buf {
+    fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    fn copy_to(&mut self, dst: &mut [u8]) -> usize {
+        let n = cmp::min(dst.len(), self.data.len() - self.pos);
+        dst[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+        self.pos += n;
+        n
+    }
+
+    /// Drop the unread data, returning the offset to move the cursor back to the caller's
+    /// position
+    fn discard(&mut self) -> i64 {
+        let unread = self.data.len() - self.pos;
+        self.clear();
+        -(unread as i64)
+    }
+
+    fn clear(&mut self) {
+        self.data.clear();
+        self.pos = 0;
+    }
+}
+
+impl AsyncRead for File {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        dst: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        loop {
+            match this.state {
+                State::Idle(Some(ref mut buf)) if !buf.is_empty() || dst.is_empty() => {
+                    return Poll::Ready(Ok(buf.copy_to(dst)));
+                }
+                State::Idle(_) => this.start_read(dst.len()),
+                State::Busy(ref mut fut) => {
+                    let (op, mut buf) = ready!(fut.poll_unpin(cx));
+
+                    let res = match op {
+                        Operation::Read(Ok(_)) => Ok(buf.copy_to(dst)),
+                        Operation::Read(Err(e)) | Operation::Write(Err(e)) => Err(e),
+                        // Another operation is completed, the read is to be started
+                        Operation::Write(Ok(_)) | Operation::Seek(_) => {
+                            this.state = State::Idle(Some(buf));
+                            continue;
+                        }
+                    };
+
+                    this.state = State::Idle(Some(buf));
+                    return Poll::Ready(res);
+                }
+            }
+        }
+    }
+}
+
+impl AsyncWrite for File {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        src: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // The operation in progress is completed first, reporting the error of the previous write
+        ready!(this.poll_idle(cx))?;
+
+        // The data is accepted right away and written in background, so nothing is left behind
+        // if the caller gives up on the write
+        Poll::Ready(Ok(this.start_write(src)))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // The data is written by the operation itself, so it's only to be completed
+        self.get_mut().poll_idle(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl AsyncSeek for File {
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+
+        loop {
+            match this.state {
+                State::Idle(_) => this.start_seek(pos),
+                State::Busy(ref mut fut) => {
+                    let (op, buf) = ready!(fut.poll_unpin(cx));
+                    this.state = State::Idle(Some(buf));
+
+                    match op {
+                        Operation::Seek(res) => return Poll::Ready(res),
+                        Operation::Write(Err(e)) => return Poll::Ready(Err(e)),
+                        // Another operation is completed, the seek is to be started
+                        Operation::Read(_) | Operation::Write(Ok(_)) => continue,
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for File {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.std.fmt(f)
+    }
+}
+
+impl AsRawFd for File {
+    fn as_raw_fd(&self) -> RawFd {
+        self.std.as_raw_fd()
+    }
+}
+
+impl AsFd for File {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.std.as_fd()
+    }
+}
+
+impl From<StdFile> for File {
+    fn from(file: StdFile) -> Self {
+        Self::from_std(file)
+    }
+}
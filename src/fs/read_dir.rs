@@ -0,0 +1,73 @@
+use super::DirEntry;
+use crate::{rt::handle::JoinHandle, task::spawn_blocking};
+use futures::{FutureExt, Stream};
+use std::{
+    collections::VecDeque,
+    fs, io,
+    path::Path,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// Number of the entries read by the single blocking call
+const CHUNK_SIZE: usize = 32;
+
+type Chunk = VecDeque<io::Result<DirEntry>>;
+
+/// Stream of the directory entries
+pub async fn read_dir(path: impl AsRef<Path>) -> io::Result<ReadDir> {
+    let path = path.as_ref().to_owned();
+    let inner = super::asyncify(move || fs::read_dir(path)).await?;
+
+    Ok(ReadDir {
+        chunk: VecDeque::new(),
+        state: State::Idle(Some(inner)),
+    })
+}
+
+pub struct ReadDir {
+    chunk: Chunk,
+    state: State,
+}
+
+enum State {
+    /// `None` once the directory is exhausted
+    Idle(Option<fs::ReadDir>),
+    Busy(JoinHandle<(Option<fs::ReadDir>, Chunk)>),
+}
+
+impl Stream for ReadDir {
+    type Item = io::Result<DirEntry>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(entry) = self.chunk.pop_front() {
+                return Poll::Ready(Some(entry));
+            }
+
+            match self.state {
+                State::Idle(ref mut inner) => {
+                    let Some(mut inner) = inner.take() else {
+                        return Poll::Ready(None);
+                    };
+
+                    self.state = State::Busy(spawn_blocking(move || {
+                        let chunk = inner.by_ref().take(CHUNK_SIZE).collect::<Chunk>();
+                        let inner = (chunk.len() == CHUNK_SIZE).then_some(inner);
+                        (inner, chunk)
+                    }));
+                }
+                State::Busy(ref mut handle) => match ready!(handle.poll_unpin(cx)) {
+                    Ok((inner, chunk)) => {
+                        self.chunk = chunk;
+                        self.state = State::Idle(inner);
+                    }
+                    Err(e) => {
+                        self.state = State::Idle(None);
+                        return Poll::Ready(Some(Err(io::Error::other(e))));
+                    }
+                },
+            }
+        }
+    }
+}
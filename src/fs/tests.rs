@@ -0,0 +1,271 @@
+use crate::fs::{self, File};
+use futures::{executor::block_on, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, StreamExt};
+use std::{io::SeekFrom, path::PathBuf, task::Poll};
+
+fn temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("asynk-fs-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn test_file() {
+    crate::test_runtime();
+
+    let dir = temp_dir("file");
+    let handle = crate::spawn(async move {
+        fs::create_dir_all(&dir).await.unwrap();
+        let path = dir.join("file");
+
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(&path)
+            .await
+            .unwrap();
+        file.write_all(b"hello, world").await.unwrap();
+        file.flush().await.unwrap();
+
+        file.seek(SeekFrom::Start(0)).await.unwrap();
+        let mut hello = [0; 5];
+        file.read_exact(&mut hello).await.unwrap();
+
+        // Write lands right after the data read, not after the data read ahead
+        file.write_all(b"!").await.unwrap();
+        file.flush().await.unwrap();
+        drop(file);
+
+        let contents = fs::read_to_string(&path).await.unwrap();
+        fs::remove_dir_all(&dir).await.unwrap();
+        (hello, contents)
+    });
+
+    let (hello, contents) = block_on(handle).unwrap();
+    assert_eq!(&hello, b"hello");
+    assert_eq!(contents, "hello! world");
+}
+
+#[test]
+fn test_abandoned_write() {
+    crate::test_runtime();
+
+    let dir = temp_dir("abandoned");
+    let handle = crate::spawn(async move {
+        fs::create_dir_all(&dir).await.unwrap();
+        let path = dir.join("file");
+        let mut file = File::create(&path).await.unwrap();
+
+        // The write future is dropped after the first poll, as the losing `select!` branch does
+        let first = futures::poll!(file.write(b"first"));
+
+        file.write_all(b" second").await.unwrap();
+        file.flush().await.unwrap();
+        drop(file);
+
+        let contents = fs::read_to_string(&path).await.unwrap();
+        fs::remove_dir_all(&dir).await.unwrap();
+        (first.map(Result::unwrap), contents)
+    });
+
+    assert_eq!(
+        block_on(handle).unwrap(),
+        (Poll::Ready(5), "first second".to_owned())
+    );
+}
+
+#[test]
+fn test_into_std_after_cancel() {
+    use std::io::Seek;
+
+    crate::test_runtime();
+
+    let dir = temp_dir("into-std");
+    let handle = crate::spawn(async move {
+        fs::create_dir_all(&dir).await.unwrap();
+        let path = dir.join("file");
+        fs::write(&path, b"hello").await.unwrap();
+
+        // The rest of the file is read ahead, so the cursor is to be moved back
+        let mut file = File::open(&path).await.unwrap();
+        let mut buf = [0; 1];
+        file.read_exact(&mut buf).await.unwrap();
+
+        // The operations are cancelled while their blocking parts may still run
+        let _ = futures::poll!(Box::pin(file.sync_all()));
+        let _ = futures::poll!(Box::pin(file.metadata()));
+
+        let mut std = file.into_std().await;
+        fs::remove_dir_all(&dir).await.unwrap();
+        std.stream_position().unwrap()
+    });
+
+    assert_eq!(block_on(handle).unwrap(), 1);
+}
+
+#[test]
+fn test_read_dir() {
+    crate::test_runtime();
+
+    let dir = temp_dir("read-dir");
+    let handle = crate::spawn(async move {
+        fs::create_dir_all(&dir).await.unwrap();
+        for i in 0..40 {
+            fs::write(dir.join(i.to_string()), b"").await.unwrap();
+        }
+        fs::rename(dir.join("0"), dir.join("renamed"))
+            .await
+            .unwrap();
+
+        let mut names = fs::read_dir(&dir)
+            .await
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        names.sort();
+
+        fs::remove_dir_all(&dir).await.unwrap();
+        names
+    });
+
+    let names = block_on(handle).unwrap();
+    assert_eq!(names.len(), 40);
+    assert!(names.contains(&"renamed".to_string()) && !names.contains(&"0".to_string()));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_watch() {
+    use crate::fs::{EventMask, WatchMask};
+
+    crate::test_runtime();
+
+    let dir = temp_dir("watch");
+    let handle = crate::spawn(async move {
+        fs::create_dir_all(&dir).await.unwrap();
+        let mut watcher = fs::watch(
+            &dir,
+            WatchMask::CREATE | WatchMask::MODIFY | WatchMask::RECURSIVE,
+        )
+        .await
+        .unwrap();
+
+        let sub = dir.join("sub");
+        fs::create_dir(&sub).await.unwrap();
+        let created = watcher.next().await.unwrap().unwrap();
+
+        // The subdirectory is watched once its creation is received
+        let file = sub.join("file");
+        fs::write(&file, b"").await.unwrap();
+        for _ in 0..10 {
+            fs::write(&file, b"data").await.unwrap();
+        }
+
+        let mut events = Vec::new();
+        while events.len() < 2 {
+            events.push(watcher.next().await.unwrap().unwrap());
+        }
+
+        fs::remove_dir_all(&dir).await.unwrap();
+        (sub, file, created, events)
+    });
+
+    let (sub, file, created, events) = block_on(handle).unwrap();
+    assert_eq!(
+        (created.path, created.mask),
+        (sub, EventMask::CREATE | EventMask::IS_DIR)
+    );
+    assert_eq!(
+        (&events[0].path, events[0].mask),
+        (&file, EventMask::CREATE)
+    );
+    assert_eq!(
+        (&events[1].path, events[1].mask),
+        (&file, EventMask::MODIFY)
+    );
+}
+
+#[test]
+fn test_watch_interleaved() {
+    use crate::fs::{EventMask, WatchMask};
+
+    crate::test_runtime();
+
+    let dir = temp_dir("watch-interleaved");
+    let handle = crate::spawn(async move {
+        fs::create_dir_all(&dir).await.unwrap();
+        let file = dir.join("file");
+        fs::write(&file, b"").await.unwrap();
+
+        let mut watcher = fs::watch(
+            &dir,
+            WatchMask::MODIFY | WatchMask::DELETE | WatchMask::CREATE,
+        )
+        .await
+        .unwrap();
+
+        // Only the repeated events are coalesced, the last modification is still received
+        fs::write(&file, b"first").await.unwrap();
+        fs::remove_file(&file).await.unwrap();
+        fs::write(&file, b"second").await.unwrap();
+
+        let mut masks = Vec::new();
+        while masks.len() < 4 {
+            let event = watcher.next().await.unwrap().unwrap();
+            assert_eq!(event.path, file);
+            masks.push(event.mask);
+        }
+
+        fs::remove_dir_all(&dir).await.unwrap();
+        masks
+    });
+
+    assert_eq!(
+        block_on(handle).unwrap(),
+        [
+            EventMask::MODIFY,
+            EventMask::DELETE,
+            EventMask::CREATE,
+            EventMask::MODIFY
+        ]
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_watch_moved_tree() {
+    use crate::fs::{EventMask, WatchMask};
+    use futures::future::{self, Either};
+    use futures_timer::Delay;
+    use std::time::Duration;
+
+    crate::test_runtime();
+
+    let dir = temp_dir("watch-tree");
+    let outside = temp_dir("watch-tree-outside");
+    let handle = crate::spawn(async move {
+        fs::create_dir_all(&dir).await.unwrap();
+        fs::create_dir_all(outside.join("nested")).await.unwrap();
+        let mut watcher = fs::watch(&dir, WatchMask::MODIFY | WatchMask::RECURSIVE)
+            .await
+            .unwrap();
+
+        // The nested directory already exists, so it's watched by the walk in background
+        let moved = dir.join("moved");
+        fs::rename(&outside, &moved).await.unwrap();
+
+        let file = moved.join("nested").join("file");
+        let event = loop {
+            fs::write(&file, b"data").await.unwrap();
+            let delay = Delay::new(Duration::from_millis(10));
+            if let Either::Left((event, _)) = future::select(watcher.next(), delay).await {
+                break event.unwrap().unwrap();
+            }
+        };
+
+        fs::remove_dir_all(&dir).await.unwrap();
+        (file, event)
+    });
+
+    let (file, event) = block_on(handle).unwrap();
+    assert_eq!((event.path, event.mask), (file, EventMask::MODIFY));
+}
@@ -0,0 +1,304 @@
+use crate::{io::unix::AsyncFd, rt::handle::JoinHandle, task::spawn_blocking};
+use bitflags::bitflags;
+use futures::{FutureExt, Stream};
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    ffi::{CString, OsStr},
+    fs, io, mem,
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd},
+        unix::ffi::OsStrExt,
+    },
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+bitflags! {
+    /// Events to watch for
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct WatchMask: u32 {
+        const ACCESS = libc::IN_ACCESS;
+        const MODIFY = libc::IN_MODIFY;
+        const ATTRIB = libc::IN_ATTRIB;
+        const CLOSE_WRITE = libc::IN_CLOSE_WRITE;
+        const CLOSE_NOWRITE = libc::IN_CLOSE_NOWRITE;
+        const OPEN = libc::IN_OPEN;
+        const MOVED_FROM = libc::IN_MOVED_FROM;
+        const MOVED_TO = libc::IN_MOVED_TO;
+        const CREATE = libc::IN_CREATE;
+        const DELETE = libc::IN_DELETE;
+        const DELETE_SELF = libc::IN_DELETE_SELF;
+        const MOVE_SELF = libc::IN_MOVE_SELF;
+        const ALL_EVENTS = libc::IN_ALL_EVENTS;
+        /// Watch the subdirectories as well, including the ones created later
+        const RECURSIVE = 1 << 16;
+    }
+}
+
+bitflags! {
+    /// Kind of the event
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct EventMask: u32 {
+        const ACCESS = libc::IN_ACCESS;
+        const MODIFY = libc::IN_MODIFY;
+        const ATTRIB = libc::IN_ATTRIB;
+        const CLOSE_WRITE = libc::IN_CLOSE_WRITE;
+        const CLOSE_NOWRITE = libc::IN_CLOSE_NOWRITE;
+        const OPEN = libc::IN_OPEN;
+        const MOVED_FROM = libc::IN_MOVED_FROM;
+        const MOVED_TO = libc::IN_MOVED_TO;
+        const CREATE = libc::IN_CREATE;
+        const DELETE = libc::IN_DELETE;
+        const DELETE_SELF = libc::IN_DELETE_SELF;
+        const MOVE_SELF = libc::IN_MOVE_SELF;
+        /// File system containing the watched path was unmounted
+        const UNMOUNT = libc::IN_UNMOUNT;
+        /// Kernel event queue overflowed, the events are lost
+        const Q_OVERFLOW = libc::IN_Q_OVERFLOW;
+        /// Watch was removed
+        const IGNORED = libc::IN_IGNORED;
+        /// Subject of the event is a directory
+        const IS_DIR = libc::IN_ISDIR;
+    }
+}
+
+/// File system change
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Event {
+    pub path: PathBuf,
+    pub mask: EventMask,
+    /// Links `MOVED_FROM` with the corresponding `MOVED_TO`, zero for the other events
+    pub cookie: u32,
+}
+
+/// Watch the file or directory for changes with inotify.
+///
+/// The unread event repeating the one before it is coalesced with it, as the kernel does, so the
+/// rapid modifications of the same file are received as one event.
+pub async fn watch(path: impl AsRef<Path>, mask: WatchMask) -> io::Result<Watcher> {
+    let path = path.as_ref().to_owned();
+
+    // Initial watches are added on the blocking pool, as the recursive ones walk the directories
+    let (fd, watches) = super::asyncify(move || {
+        // SAFETY: the call has no memory safety preconditions
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the descriptor has just been created and is owned by nobody else
+        let fd = Arc::new(unsafe { OwnedFd::from_raw_fd(fd) });
+
+        let watches = Arc::default();
+        add_watch(&fd, &path, mask, &watches)?;
+        Ok((fd, watches))
+    })
+    .await?;
+
+    Ok(Watcher {
+        fd: AsyncFd::new(fd)?,
+        mask,
+        watches,
+        events: VecDeque::new(),
+        new_dirs: Vec::new(),
+        walk: None,
+    })
+}
+
+/// Stream of the file system changes
+pub struct Watcher {
+    fd: AsyncFd<Arc<OwnedFd>>,
+    mask: WatchMask,
+    /// Watched paths by the watch descriptors, shared with the directory walk
+    watches: Arc<Mutex<HashMap<i32, PathBuf>>>,
+    events: VecDeque<Event>,
+    /// New directories waiting for the walk of their subdirectories
+    new_dirs: Vec<PathBuf>,
+    /// Walk of the new directories in progress
+    walk: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    /// Watch the subdirectories of the new directories on the blocking pool, one walk at a time
+    fn poll_walk(&mut self, cx: &mut Context<'_>) {
+        if let Some(ref mut walk) = self.walk {
+            if walk.poll_unpin(cx).is_pending() {
+                return;
+            }
+            self.walk = None;
+        }
+
+        if self.new_dirs.is_empty() {
+            return;
+        }
+
+        let dirs = mem::take(&mut self.new_dirs);
+        let fd = self.fd.get_ref().clone();
+        let watches = self.watches.clone();
+        let mask = self.mask;
+
+        // Errors are ignored, as the directories may be already removed
+        let mut walk = spawn_blocking(move || {
+            for dir in dirs {
+                add_subdir_watches(&fd, &dir, mask, &watches).ok();
+            }
+        });
+        if walk.poll_unpin(cx).is_pending() {
+            self.walk = Some(walk);
+        }
+    }
+
+    /// Read the pending events into the queue, coalescing the ones repeating the last queued
+    fn read_events(&mut self, buf: &[u8]) {
+        let mut watches = self.watches.lock();
+        let mut offset = 0;
+
+        while offset + mem::size_of::<libc::inotify_event>() <= buf.len() {
+            // SAFETY: the kernel writes the complete events, the header is read unaligned
+            let raw = unsafe {
+                std::ptr::read_unaligned(buf[offset..].as_ptr().cast::<libc::inotify_event>())
+            };
+            let name_start = offset + mem::size_of::<libc::inotify_event>();
+            let name = &buf[name_start..name_start + raw.len as usize];
+            offset = name_start + raw.len as usize;
+
+            // The name is padded with nul bytes
+            let name = OsStr::from_bytes(name.split(|&b| b == 0).next().unwrap_or_default());
+            let mask = EventMask::from_bits_truncate(raw.mask);
+
+            let path = match watches.get(&raw.wd) {
+                Some(dir) if name.is_empty() => dir.clone(),
+                Some(dir) => dir.join(name),
+                None if mask.contains(EventMask::Q_OVERFLOW) => PathBuf::new(),
+                None => continue,
+            };
+
+            if mask.contains(EventMask::IGNORED) {
+                watches.remove(&raw.wd);
+            }
+
+            // New subdirectories are watched at once, their contents are walked later. Errors
+            // are ignored, as the directory may be already removed.
+            if self.mask.contains(WatchMask::RECURSIVE)
+                && mask.contains(EventMask::IS_DIR)
+                && mask.intersects(EventMask::CREATE | EventMask::MOVED_TO)
+                && add_one_watch(self.fd.get_ref(), &path, self.mask, &mut watches).is_ok()
+            {
+                self.new_dirs.push(path.clone());
+            }
+
+            // Only the requested events are delivered, the recursive watch may add more
+            let requested = EventMask::from_bits_truncate(self.mask.bits())
+                | EventMask::UNMOUNT
+                | EventMask::Q_OVERFLOW
+                | EventMask::IGNORED;
+            if !mask.intersects(requested) {
+                continue;
+            }
+
+            let event = Event {
+                path,
+                mask,
+                cookie: raw.cookie,
+            };
+            if self.events.back() != Some(&event) {
+                self.events.push_back(event);
+            }
+        }
+    }
+}
+
+impl Stream for Watcher {
+    type Item = io::Result<Event>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            this.poll_walk(cx);
+
+            if let Some(event) = this.events.pop_front() {
+                return Poll::Ready(Some(Ok(event)));
+            }
+
+            // Large enough for a few events with the longest names
+            let mut buf = [0u8; 4096];
+            let mut guard = ready!(this.fd.poll_read_ready(cx))?;
+
+            let res = guard.try_io(|fd| {
+                // SAFETY: the buffer is valid for writing up to its length
+                let n = unsafe { libc::read(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+                if n < 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(n as usize)
+                }
+            });
+
+            match res {
+                Ok(Ok(n)) => this.read_events(&buf[..n]),
+                Ok(Err(e)) => return Poll::Ready(Some(Err(e))),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+/// Add the watch for the path, walking the subdirectories if the watch is recursive
+fn add_watch(
+    fd: &OwnedFd,
+    path: &Path,
+    mask: WatchMask,
+    watches: &Mutex<HashMap<i32, PathBuf>>,
+) -> io::Result<()> {
+    // The lock is held until the watch is recorded, so its first events can't be read before
+    add_one_watch(fd, path, mask, &mut watches.lock())?;
+
+    if mask.contains(WatchMask::RECURSIVE) && path.is_dir() {
+        add_subdir_watches(fd, path, mask, watches)?;
+    }
+
+    Ok(())
+}
+
+/// Add the watches for the subdirectories of the directory
+fn add_subdir_watches(
+    fd: &OwnedFd,
+    path: &Path,
+    mask: WatchMask,
+    watches: &Mutex<HashMap<i32, PathBuf>>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            add_watch(fd, &entry.path(), mask, watches)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Add the watch for the path only
+fn add_one_watch(
+    fd: &OwnedFd,
+    path: &Path,
+    mask: WatchMask,
+    watches: &mut HashMap<i32, PathBuf>,
+) -> io::Result<()> {
+    let mut inotify_mask = (mask - WatchMask::RECURSIVE).bits();
+    if mask.contains(WatchMask::RECURSIVE) {
+        inotify_mask |= libc::IN_CREATE | libc::IN_MOVED_TO;
+    }
+
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    // SAFETY: the path is the valid nul-terminated string
+    let wd = unsafe { libc::inotify_add_watch(fd.as_raw_fd(), c_path.as_ptr(), inotify_mask) };
+    if wd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    watches.insert(wd, path.to_owned());
+
+    Ok(())
+}
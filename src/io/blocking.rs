@@ -0,0 +1,158 @@
+use crate::{rt::handle::JoinHandle, task::spawn_blocking};
+use futures::{AsyncRead, AsyncWrite, FutureExt};
+use std::{
+    cmp,
+    io::{self, Read, Write},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// Maximum size of the single read or write
+const MAX_BUF: usize = 2 * 1024 * 1024;
+
+/// Blocking reader or writer driven by the blocking pool.
+///
+/// Only one operation may be in progress. The data read ahead is returned by the next reads,
+/// the written data is accepted at once and its write error is returned by the next operation.
+pub(crate) struct Blocking<T> {
+    state: State<T>,
+}
+
+enum State<T> {
+    Idle(Option<(T, Buf)>),
+    Busy(JoinHandle<(io::Result<usize>, T, Buf)>),
+}
+
+#[derive(Default)]
+struct Buf {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl<T> Blocking<T>
+where
+    T: Send + 'static,
+{
+    pub fn new(inner: T) -> Self {
+        Self {
+            state: State::Idle(Some((inner, Buf::default()))),
+        }
+    }
+
+    /// Wait for the operation in progress
+    fn poll_complete(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let handle = match self.state {
+            State::Busy(ref mut handle) => handle,
+            State::Idle(Some(_)) => return Poll::Ready(Ok(0)),
+            State::Idle(None) => return Poll::Ready(Err(lost())),
+        };
+
+        match ready!(handle.poll_unpin(cx)) {
+            Ok((res, inner, buf)) => {
+                self.state = State::Idle(Some((inner, buf)));
+                Poll::Ready(res)
+            }
+            Err(e) => {
+                // The object went down with the task, every further operation fails
+                self.state = State::Idle(None);
+                Poll::Ready(Err(io::Error::other(e)))
+            }
+        }
+    }
+
+    fn take(&mut self) -> io::Result<(T, Buf)> {
+        match self.state {
+            State::Idle(ref mut inner) => inner.take().ok_or_else(lost),
+            State::Busy(_) => unreachable!("blocking I/O is in progress"),
+        }
+    }
+}
+
+impl<T> AsyncRead for Blocking<T>
+where
+    T: Read + Send + Unpin + 'static,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        dst: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        loop {
+            if let State::Idle(Some((_, ref mut buf))) = this.state {
+                if buf.pos != buf.data.len() || dst.is_empty() {
+                    let n = cmp::min(dst.len(), buf.data.len() - buf.pos);
+                    dst[..n].copy_from_slice(&buf.data[buf.pos..buf.pos + n]);
+                    buf.pos += n;
+                    return Poll::Ready(Ok(n));
+                }
+            }
+
+            if let State::Busy(_) = this.state {
+                if ready!(this.poll_complete(cx))? == 0 {
+                    return Poll::Ready(Ok(0));
+                }
+                continue;
+            }
+
+            let (mut inner, mut buf) = this.take()?;
+            let len = cmp::min(dst.len(), MAX_BUF);
+
+            this.state = State::Busy(spawn_blocking(move || {
+                buf.data.resize(len, 0);
+                buf.pos = 0;
+
+                let res = inner.read(&mut buf.data);
+                buf.data.truncate(*res.as_ref().unwrap_or(&0));
+                (res, inner, buf)
+            }));
+        }
+    }
+}
+
+impl<T> AsyncWrite for Blocking<T>
+where
+    T: Write + Send + Unpin + 'static,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        src: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // The previous write is completed first, reporting its error
+        if let State::Busy(_) = this.state {
+            ready!(this.poll_complete(cx))?;
+        }
+
+        let (mut inner, mut buf) = this.take()?;
+        let n = cmp::min(src.len(), MAX_BUF);
+        buf.data.extend_from_slice(&src[..n]);
+
+        // The data is accepted right away and written in background
+        this.state = State::Busy(spawn_blocking(move || {
+            let res = inner
+                .write_all(&buf.data)
+                .and_then(|_| inner.flush())
+                .map(|_| n);
+            buf.data.clear();
+            (res, inner, buf)
+        }));
+
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_complete(cx).map_ok(|_| ())
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+fn lost() -> io::Error {
+    io::Error::other("blocking I/O object is lost after its task failed")
+}
@@ -0,0 +1,8 @@
+mod reader;
+mod stream;
+mod writer;
+
+pub use self::{reader::BufReader, stream::BufStream, writer::BufWriter};
+
+/// Default buffer capacity
+const DEFAULT_CAPACITY: usize = 8 * 1024;
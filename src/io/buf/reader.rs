@@ -0,0 +1,190 @@
+use super::DEFAULT_CAPACITY;
+use futures::{io::Lines, AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncSeek, AsyncWrite};
+use std::{
+    cmp, fmt,
+    io::{self, IoSlice, SeekFrom},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// Reader buffering the data of the inner one, so the small reads don't turn into the syscalls
+/// each.
+///
+/// Implements `AsyncBufRead`, so the methods of `futures::AsyncBufReadExt` are available as
+/// well. Writes are passed to the inner object as they are.
+pub struct BufReader<R> {
+    inner: R,
+    buf: Box<[u8]>,
+    pos: usize,
+    filled: usize,
+}
+
+impl<R: AsyncRead + Unpin> BufReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY, inner)
+    }
+
+    pub fn with_capacity(capacity: usize, inner: R) -> Self {
+        Self {
+            inner,
+            buf: vec![0; capacity].into_boxed_slice(),
+            pos: 0,
+            filled: 0,
+        }
+    }
+
+    /// Read the bytes until the newline, appending them to `buf` including the newline.
+    /// Returns the number of bytes read, zero at the end of the stream.
+    pub async fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        AsyncBufReadExt::read_line(self, buf).await
+    }
+
+    /// Stream of the lines without the trailing newlines
+    pub fn lines(self) -> Lines<Self> {
+        AsyncBufReadExt::lines(self)
+    }
+
+    /// Fill the buffer if it's empty, returning the buffered data
+    pub async fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        AsyncBufReadExt::fill_buf(self).await
+    }
+
+    /// Mark the bytes as read, so they aren't returned anymore
+    pub fn consume(&mut self, amt: usize) {
+        AsyncBufRead::consume(Pin::new(self), amt)
+    }
+}
+
+impl<R> BufReader<R> {
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Reading from the inner object directly may lose the buffered data
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Take the inner object out. The buffered data is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Data which is buffered but not read yet
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.filled]
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn discard_buffer(&mut self) {
+        self.pos = 0;
+        self.filled = 0;
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for BufReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        dst: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // Large reads bypass the empty buffer, copying would only slow them down
+        if this.pos == this.filled && dst.len() >= this.buf.len() {
+            let res = ready!(Pin::new(&mut this.inner).poll_read(cx, dst));
+            this.discard_buffer();
+            return Poll::Ready(res);
+        }
+
+        let available = ready!(Pin::new(&mut *this).poll_fill_buf(cx))?;
+        let n = cmp::min(available.len(), dst.len());
+        dst[..n].copy_from_slice(&available[..n]);
+        Pin::new(this).consume(n);
+
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncBufRead for BufReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+
+        if this.pos == this.filled {
+            let n = ready!(Pin::new(&mut this.inner).poll_read(cx, &mut this.buf))?;
+            this.pos = 0;
+            this.filled = n;
+        }
+
+        Poll::Ready(Ok(&this.buf[this.pos..this.filled]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        this.pos = cmp::min(this.pos + amt, this.filled);
+    }
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncSeek for BufReader<R> {
+    /// Seek the inner object. `SeekFrom::Current` is relative to the data read by the caller,
+    /// the buffered data is discarded.
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+
+        let pos = match pos {
+            SeekFrom::Current(offset) => {
+                SeekFrom::Current(offset - (this.filled - this.pos) as i64)
+            }
+            pos => pos,
+        };
+
+        let res = ready!(Pin::new(&mut this.inner).poll_seek(cx, pos));
+        this.discard_buffer();
+        Poll::Ready(res)
+    }
+}
+
+impl<R: AsyncWrite + Unpin> AsyncWrite for BufReader<R> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for BufReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufReader")
+            .field("inner", &self.inner)
+            .field(
+                "buffer",
+                &format_args!("{}/{}", self.filled - self.pos, self.buf.len()),
+            )
+            .finish()
+    }
+}
@@ -0,0 +1,98 @@
+use super::{BufReader, BufWriter, DEFAULT_CAPACITY};
+use futures::{io::Lines, AsyncBufRead, AsyncRead, AsyncWrite};
+use std::{
+    fmt, io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Stream buffering both the reads and the writes, e.g. for the line-based protocols over
+/// `TcpStream`
+pub struct BufStream<S>(BufReader<BufWriter<S>>);
+
+impl<S: AsyncRead + AsyncWrite + Unpin> BufStream<S> {
+    pub fn new(inner: S) -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY, DEFAULT_CAPACITY, inner)
+    }
+
+    pub fn with_capacity(read_capacity: usize, write_capacity: usize, inner: S) -> Self {
+        Self(BufReader::with_capacity(
+            read_capacity,
+            BufWriter::with_capacity(write_capacity, inner),
+        ))
+    }
+
+    /// See `BufReader::read_line`
+    pub async fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        futures::AsyncBufReadExt::read_line(self, buf).await
+    }
+
+    /// Stream of the lines without the trailing newlines
+    pub fn lines(self) -> Lines<Self> {
+        futures::AsyncBufReadExt::lines(self)
+    }
+
+    /// Fill the read buffer if it's empty, returning the buffered data
+    pub async fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf().await
+    }
+}
+
+impl<S> BufStream<S> {
+    pub fn get_ref(&self) -> &S {
+        self.0.get_ref().get_ref()
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        self.0.get_mut().get_mut()
+    }
+
+    /// Take the inner stream out. The buffered data of both directions is lost.
+    pub fn into_inner(self) -> S {
+        self.0.into_inner().into_inner()
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncRead for BufStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncBufRead for BufStream<S> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        Pin::new(&mut self.get_mut().0).poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        Pin::new(&mut self.get_mut().0).consume(amt)
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncWrite for BufStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_close(cx)
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for BufStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BufStream").field(&self.0).finish()
+    }
+}
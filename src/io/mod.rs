@@ -0,0 +1,15 @@
+pub mod unix;
+
+mod blocking;
+mod buf;
+mod stdio;
+#[cfg(target_os = "linux")]
+mod zero_copy;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::buf::{BufReader, BufStream, BufWriter};
+pub use self::stdio::{stderr, stdin, stdout, Stderr, Stdin, Stdout};
+#[cfg(target_os = "linux")]
+pub use self::zero_copy::{sendfile, splice, AsyncIoFd};
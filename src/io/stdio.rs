@@ -0,0 +1,158 @@
+use super::blocking::Blocking;
+use crate::reactor::io_handle::IoHandle;
+use futures::{AsyncRead, AsyncWrite};
+use mio::unix::pipe::{Receiver, Sender};
+use std::{
+    ffi::CString,
+    fmt, io,
+    os::fd::{FromRawFd, RawFd},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Standard input of the process
+pub struct Stdin(Inner<Receiver, io::Stdin>);
+
+/// Standard output of the process
+pub struct Stdout(Inner<Sender, io::Stdout>);
+
+/// Standard error of the process
+pub struct Stderr(Inner<Sender, io::Stderr>);
+
+/// Standard input of the process.
+///
+/// If it's a pipe or a terminal, it's reopened in the non-blocking mode and registered in the
+/// reactor. Otherwise, e.g. for the regular files, the reads are performed by the blocking pool.
+pub fn stdin() -> Stdin {
+    Stdin(Inner::new(libc::STDIN_FILENO, libc::O_RDONLY, io::stdin))
+}
+
+/// Standard output of the process, see `stdin` for the way it's written
+pub fn stdout() -> Stdout {
+    Stdout(Inner::new(libc::STDOUT_FILENO, libc::O_WRONLY, io::stdout))
+}
+
+/// Standard error of the process, see `stdin` for the way it's written
+pub fn stderr() -> Stderr {
+    Stderr(Inner::new(libc::STDERR_FILENO, libc::O_WRONLY, io::stderr))
+}
+
+enum Inner<S: mio::event::Source, B> {
+    Async(IoHandle<S>),
+    Blocking(Blocking<B>),
+}
+
+impl<S, B> Inner<S, B>
+where
+    S: mio::event::Source + FromRawFd,
+    B: Send + 'static,
+{
+    fn new(fd: RawFd, flags: libc::c_int, blocking: fn() -> B) -> Self {
+        match reopen(fd, flags).and_then(IoHandle::new) {
+            Ok(handle) => Self::Async(handle),
+            Err(_) => Self::Blocking(Blocking::new(blocking())),
+        }
+    }
+}
+
+/// Open the descriptor anew in the non-blocking mode. The standard streams share the file
+/// description with the parent process, so the mode can't be changed on them directly.
+pub(super) fn reopen<S: FromRawFd>(fd: RawFd, flags: libc::c_int) -> io::Result<S> {
+    // SAFETY: all-zero is the valid `stat`, which is filled by the call
+    let mut stat = unsafe { std::mem::zeroed::<libc::stat>() };
+    // SAFETY: `stat` is valid for writing
+    if unsafe { libc::fstat(fd, &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // Regular files are always ready, the reactor can't wait for them
+    let kind = stat.st_mode & libc::S_IFMT;
+    if kind != libc::S_IFIFO && kind != libc::S_IFCHR {
+        return Err(io::ErrorKind::Unsupported.into());
+    }
+
+    let path = CString::new(format!("/proc/self/fd/{}", fd)).expect("path has no nul bytes");
+    // SAFETY: the path is the valid nul-terminated string
+    let fd = unsafe {
+        libc::open(
+            path.as_ptr(),
+            flags | libc::O_NONBLOCK | libc::O_CLOEXEC | libc::O_NOCTTY,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: the descriptor has just been opened and is owned by nobody else
+    Ok(unsafe { S::from_raw_fd(fd) })
+}
+
+impl AsyncRead for Stdin {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut().0 {
+            Inner::Async(ref handle) => handle.poll_read(cx, buf),
+            Inner::Blocking(ref mut blocking) => Pin::new(blocking).poll_read(cx, buf),
+        }
+    }
+}
+
+macro_rules! impl_write {
+    ($($ty:ty),*) => {$(
+        impl AsyncWrite for $ty {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                match self.get_mut().0 {
+                    Inner::Async(ref handle) => handle.poll_write(cx, buf),
+                    Inner::Blocking(ref mut blocking) => Pin::new(blocking).poll_write(cx, buf),
+                }
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                match self.get_mut().0 {
+                    Inner::Async(ref handle) => handle.poll_flush(cx),
+                    Inner::Blocking(ref mut blocking) => Pin::new(blocking).poll_flush(cx),
+                }
+            }
+
+            fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                self.poll_flush(cx)
+            }
+        }
+    )*};
+}
+
+impl_write!(Stdout, Stderr);
+
+impl<S: mio::event::Source, B> fmt::Debug for Inner<S, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Async(_) => f.write_str("Async"),
+            Self::Blocking(_) => f.write_str("Blocking"),
+        }
+    }
+}
+
+impl fmt::Debug for Stdin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Stdin").field(&self.0).finish()
+    }
+}
+
+impl fmt::Debug for Stdout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Stdout").field(&self.0).finish()
+    }
+}
+
+impl fmt::Debug for Stderr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Stderr").field(&self.0).finish()
+    }
+}
@@ -0,0 +1,209 @@
+use super::{blocking::Blocking, stdio, BufReader, BufStream, BufWriter};
+use crate::{io::unix::AsyncFd, reactor::io_handle::IoHandle};
+use futures::{executor::block_on, AsyncReadExt, AsyncWriteExt};
+use mio::unix::pipe::{self, Receiver};
+use std::{
+    io::{self, Cursor, Read, Write},
+    os::{fd::AsRawFd, unix::net::UnixStream},
+};
+
+#[test]
+fn test_async_fd() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let (left, mut right) = UnixStream::pair().unwrap();
+        left.set_nonblocking(true).unwrap();
+        let fd = AsyncFd::new(left).unwrap();
+
+        let reader = crate::spawn(async move {
+            let mut buf = [0; 16];
+            loop {
+                let mut guard = fd.readable().await.unwrap();
+                match guard.try_io(|fd| fd.get_ref().read(&mut buf)) {
+                    Ok(n) => break buf[..n.unwrap()].to_vec(),
+                    Err(_would_block) => continue,
+                }
+            }
+        });
+
+        right.write_all(b"ping").unwrap();
+        reader.await.unwrap()
+    });
+
+    assert_eq!(block_on(handle).unwrap(), b"ping");
+}
+
+#[test]
+fn test_reopen() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let (mut sender, receiver) = pipe::new().unwrap();
+
+        // The pipe is opened anew, as the standard streams are
+        let reopened: Receiver = stdio::reopen(receiver.as_raw_fd(), libc::O_RDONLY).unwrap();
+        let reopened = IoHandle::new(reopened).unwrap();
+
+        sender.write_all(b"ping").unwrap();
+        drop((sender, receiver));
+
+        let mut buf = [0; 4];
+        futures::future::poll_fn(|cx| reopened.poll_read(cx, &mut buf))
+            .await
+            .unwrap();
+        buf
+    });
+
+    assert_eq!(&block_on(handle).unwrap(), b"ping");
+}
+
+#[test]
+fn test_blocking() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let mut reader = Blocking::new(Cursor::new(b"hello, world".to_vec()));
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf).await.unwrap();
+        buf
+    });
+
+    assert_eq!(block_on(handle).unwrap(), "hello, world");
+}
+
+#[test]
+fn test_blocking_panic() {
+    struct Panicking;
+
+    impl Write for Panicking {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            panic!("write failed");
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let mut writer = Blocking::new(Panicking);
+        writer.write_all(b"ping").await.unwrap();
+        writer.flush().await.is_err() && writer.write_all(b"ping").await.is_err()
+    });
+
+    assert!(block_on(handle).unwrap());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_sendfile_splice() {
+    use crate::net::{unix::pipe, TcpListener, TcpStream};
+
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        // Large enough to fill the socket buffers, so the transfer is resumed
+        let data = (0..4 * 1024 * 1024).map(|i| i as u8).collect::<Vec<_>>();
+        let mut file = tempfile();
+        file.write_all(&data).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        let sender = crate::spawn(async move {
+            let err = crate::io::sendfile(&file, &server, u64::MAX, 1).await;
+            assert_eq!(err.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+            let sent = crate::io::sendfile(&file, &server, 1, data.len() - 1).await;
+            (sent.unwrap(), data)
+        });
+
+        // Socket to pipe to memory
+        let (pipe_tx, mut pipe_rx) = pipe::pipe().unwrap();
+        let reader = crate::spawn(async move {
+            let mut received = Vec::new();
+            pipe_rx.read_to_end(&mut received).await.unwrap();
+            received
+        });
+
+        // The length is past the end, so the transfer is stopped by the end of the socket
+        let moved = crate::io::splice(&client, &pipe_tx, usize::MAX)
+            .await
+            .unwrap();
+        drop(pipe_tx);
+
+        let (sent, data) = sender.await.unwrap();
+        assert_eq!(moved, sent);
+        (sent, data, reader.await.unwrap())
+    });
+
+    let (sent, data, received) = block_on(handle).unwrap();
+    assert_eq!(sent, data.len() - 1);
+    assert!(received == data[1..]);
+}
+
+#[cfg(target_os = "linux")]
+fn tempfile() -> std::fs::File {
+    let path = std::env::temp_dir().join(format!("asynk-sendfile-{}", std::process::id()));
+    let file = std::fs::File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .unwrap();
+    std::fs::remove_file(path).unwrap();
+    file
+}
+
+#[test]
+fn test_buffered() {
+    use crate::net::{TcpListener, TcpStream};
+    use futures::{AsyncWriteExt, StreamExt};
+
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        // Line-based echo: each request line is answered after the flush
+        let echo = crate::spawn(async move {
+            let mut stream = BufStream::new(server);
+            let mut line = String::new();
+            while stream.read_line(&mut line).await.unwrap() != 0 {
+                stream
+                    .write_all(line.to_uppercase().as_bytes())
+                    .await
+                    .unwrap();
+                stream.flush().await.unwrap();
+                line.clear();
+            }
+        });
+
+        let (read_half, write_half) = client.into_split();
+        let mut writer = BufWriter::with_capacity(4, write_half);
+        writer.write_all(b"hello\nworld\n").await.unwrap();
+        // Closing flushes the buffered data and shuts the socket down
+        writer.close().await.unwrap();
+
+        let lines = BufReader::new(read_half)
+            .lines()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        echo.await.unwrap();
+        lines
+    });
+
+    assert_eq!(block_on(handle).unwrap(), ["HELLO", "WORLD"]);
+}
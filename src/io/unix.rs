@@ -0,0 +1,178 @@
+use crate::reactor::{
+    io_handle::IoHandle,
+    scheduled_io::{Direction, ReadyEvent},
+};
+use mio::{event::Source, unix::SourceFd, Interest, Registry, Token};
+use std::{
+    fmt,
+    future::poll_fn,
+    io::{self, ErrorKind, Result},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd},
+    task::{ready, Context, Poll},
+};
+
+/// File descriptor registered in the reactor.
+///
+/// The wrapper only tracks the readiness, the I/O itself is performed by the user on the inner
+/// object, which should be switched to the non-blocking mode beforehand.
+pub struct AsyncFd<T: AsRawFd> {
+    // Declared first, so the descriptor is deregistered before `inner` closes it
+    handle: IoHandle<Fd>,
+    inner: T,
+}
+
+/// Raw descriptor as the `mio` source
+struct Fd(RawFd);
+
+impl<T: AsRawFd> AsyncFd<T> {
+    /// Register the descriptor for both reading and writing
+    pub fn new(inner: T) -> Result<Self> {
+        let handle = IoHandle::new(Fd(inner.as_raw_fd()))?;
+        Ok(Self { handle, inner })
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Deregister the descriptor from the reactor and take the inner object out
+    pub fn into_inner(self) -> T {
+        self.handle.into_inner().ok();
+        self.inner
+    }
+
+    /// Wait until the descriptor becomes readable
+    pub async fn readable(&self) -> Result<AsyncFdReadyGuard<'_, T>> {
+        poll_fn(|cx| self.poll_read_ready(cx)).await
+    }
+
+    /// Wait until the descriptor becomes writable
+    pub async fn writable(&self) -> Result<AsyncFdReadyGuard<'_, T>> {
+        poll_fn(|cx| self.poll_write_ready(cx)).await
+    }
+
+    pub fn poll_read_ready(&self, cx: &mut Context<'_>) -> Poll<Result<AsyncFdReadyGuard<'_, T>>> {
+        self.poll_ready(cx, Direction::Read)
+    }
+
+    pub fn poll_write_ready(&self, cx: &mut Context<'_>) -> Poll<Result<AsyncFdReadyGuard<'_, T>>> {
+        self.poll_ready(cx, Direction::Write)
+    }
+
+    fn poll_ready(
+        &self,
+        cx: &mut Context<'_>,
+        direction: Direction,
+    ) -> Poll<Result<AsyncFdReadyGuard<'_, T>>> {
+        let event = ready!(self.handle.poll_ready(cx, direction));
+
+        Poll::Ready(Ok(AsyncFdReadyGuard {
+            fd: self,
+            event: Some(event),
+        }))
+    }
+}
+
+impl<T: AsRawFd> AsRawFd for AsyncFd<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl<T: AsRawFd> AsFd for AsyncFd<T> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the descriptor stays open while the inner object is alive
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
+
+impl<T: AsRawFd + fmt::Debug> fmt::Debug for AsyncFd<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncFd")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+/// Readiness of the descriptor observed by `readable` or `writable`.
+///
+/// The readiness stays set until it's cleared, so the operation which returned `WouldBlock`
+/// must be followed by `clear_ready`, otherwise the next wait completes immediately.
+pub struct AsyncFdReadyGuard<'a, T: AsRawFd> {
+    fd: &'a AsyncFd<T>,
+    // `None` once the readiness is cleared
+    event: Option<ReadyEvent>,
+}
+
+impl<'a, T: AsRawFd> AsyncFdReadyGuard<'a, T> {
+    pub fn get_ref(&self) -> &'a AsyncFd<T> {
+        self.fd
+    }
+
+    pub fn get_inner(&self) -> &'a T {
+        self.fd.get_ref()
+    }
+
+    /// Forget the observed readiness, so the next wait is completed by the new event only
+    pub fn clear_ready(&mut self) {
+        if let Some(event) = self.event.take() {
+            self.fd.handle.clear_readiness(event);
+        }
+    }
+
+    /// Perform the I/O operation on the descriptor. If it returns `WouldBlock`, the readiness
+    /// is cleared and `TryIoError` is returned, so the caller should wait again.
+    pub fn try_io<R>(
+        &mut self,
+        f: impl FnOnce(&'a AsyncFd<T>) -> Result<R>,
+    ) -> std::result::Result<Result<R>, TryIoError> {
+        match f(self.fd) {
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                self.clear_ready();
+                Err(TryIoError(()))
+            }
+            res => Ok(res),
+        }
+    }
+}
+
+impl<T: AsRawFd + fmt::Debug> fmt::Debug for AsyncFdReadyGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncFdReadyGuard")
+            .field("fd", &self.fd)
+            .finish()
+    }
+}
+
+/// The operation passed to `AsyncFdReadyGuard::try_io` would block
+#[derive(Debug, thiserror::Error)]
+#[error("operation would block")]
+pub struct TryIoError(());
+
+impl Source for Fd {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.0).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.0).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        SourceFd(&self.0).deregister(registry)
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    net::{
+        unix::{
+            pipe::{Receiver, Sender},
+            UnixStream,
+        },
+        TcpStream,
+    },
+    reactor::scheduled_io::{Direction, ReadyEvent},
+};
+use std::{
+    future::poll_fn,
+    io::{self, ErrorKind},
+    os::fd::{AsFd, AsRawFd, RawFd},
+    ptr,
+    task::{ready, Context, Poll},
+};
+
+/// Maximum size of the single `sendfile` call
+const MAX_CHUNK: usize = 0x7fff_f000;
+
+/// Descriptor registered in the reactor, which can be the end of `sendfile` or `splice`
+pub trait AsyncIoFd: AsRawFd + sealed::Sealed {}
+
+impl AsyncIoFd for TcpStream {}
+impl AsyncIoFd for UnixStream {}
+impl AsyncIoFd for Sender {}
+impl AsyncIoFd for Receiver {}
+
+/// Copy `len` bytes of the file starting at `offset` to the socket without passing them
+/// through the user space. The transfer is resumed whenever the socket becomes writable again,
+/// so it's only stopped early by the end of the file.
+///
+/// Returns the number of bytes copied. The file cursor is not changed.
+pub async fn sendfile(
+    file: &impl AsFd,
+    to: &impl AsyncIoFd,
+    offset: u64,
+    len: usize,
+) -> io::Result<usize> {
+    let file = file.as_fd().as_raw_fd();
+    let mut offset = libc::off_t::try_from(offset)
+        .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "offset is out of range"))?;
+    let mut sent = 0;
+
+    while sent < len {
+        let n = poll_fn(|cx| {
+            poll_io(cx, &[(to, Direction::Write)], || {
+                let count = (len - sent).min(MAX_CHUNK);
+                // SAFETY: `offset` is valid for writing, the descriptors are borrowed
+                cvt(unsafe { libc::sendfile(to.as_raw_fd(), file, &mut offset, count) })
+            })
+        })
+        .await?;
+
+        if n == 0 {
+            break;
+        }
+        sent += n;
+    }
+
+    Ok(sent)
+}
+
+/// Move `len` bytes between the descriptors without passing them through the user space. At
+/// least one of them must be a pipe. The transfer is resumed whenever both descriptors become
+/// ready again, so it's only stopped early by the end of `from`.
+///
+/// Returns the number of bytes moved.
+pub async fn splice(from: &impl AsyncIoFd, to: &impl AsyncIoFd, len: usize) -> io::Result<usize> {
+    let mut moved = 0;
+
+    while moved < len {
+        let n = poll_fn(|cx| {
+            poll_io(
+                cx,
+                &[(from, Direction::Read), (to, Direction::Write)],
+                || {
+                    // SAFETY: the offsets are null, the descriptors are borrowed
+                    cvt(unsafe {
+                        libc::splice(
+                            from.as_raw_fd(),
+                            ptr::null_mut(),
+                            to.as_raw_fd(),
+                            ptr::null_mut(),
+                            (len - moved).min(MAX_CHUNK),
+                            libc::SPLICE_F_MOVE | libc::SPLICE_F_NONBLOCK,
+                        )
+                    })
+                },
+            )
+        })
+        .await?;
+
+        if n == 0 {
+            break;
+        }
+        moved += n;
+    }
+
+    Ok(moved)
+}
+
+/// Wait for all of the descriptors to become ready and perform the operation. If it would
+/// block, the readiness of the descriptors which are not ready anymore is cleared and the
+/// operation is retried once. If it would block again, the readiness of all of them is cleared.
+fn poll_io(
+    cx: &mut Context<'_>,
+    fds: &[(&dyn AsyncIoFdDyn, Direction)],
+    mut f: impl FnMut() -> io::Result<usize>,
+) -> Poll<io::Result<usize>> {
+    let coop = ready!(crate::task::coop::poll_proceed(cx));
+
+    let mut retried = false;
+    loop {
+        let mut events = [None; 2];
+        for (event, &(fd, direction)) in events.iter_mut().zip(fds) {
+            *event = Some(ready!(fd.poll_ready(cx, direction)));
+        }
+
+        match f() {
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                // The operation doesn't tell which side would block, so the descriptors are asked.
+                // They may claim to be ready anyway, so the next time the reactor is waited for.
+                for (event, &(fd, direction)) in events.iter().zip(fds) {
+                    if let Some(event) = *event {
+                        if retried || !is_ready(fd.raw_fd(), direction)? {
+                            fd.clear_readiness(event);
+                        }
+                    }
+                }
+                retried = true;
+            }
+            res => {
+                coop.made_progress();
+                return Poll::Ready(res);
+            }
+        }
+    }
+}
+
+/// Whether the descriptor is ready for the direction at the moment
+fn is_ready(fd: RawFd, direction: Direction) -> io::Result<bool> {
+    let events = match direction {
+        Direction::Read => libc::POLLIN,
+        Direction::Write => libc::POLLOUT,
+    };
+    let mut pollfd = libc::pollfd {
+        fd,
+        events,
+        revents: 0,
+    };
+
+    // SAFETY: the descriptor set is valid for writing
+    if unsafe { libc::poll(&mut pollfd, 1, 0) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // Errors and hang-ups are reported by the operation itself, they don't make it ready
+    Ok(pollfd.revents & events != 0)
+}
+
+fn cvt(n: isize) -> io::Result<usize> {
+    if n < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(n as usize)
+    }
+}
+
+/// Object-safe view of `AsyncIoFd`
+trait AsyncIoFdDyn {
+    fn raw_fd(&self) -> RawFd;
+    fn poll_ready(&self, cx: &mut Context<'_>, direction: Direction) -> Poll<ReadyEvent>;
+    fn clear_readiness(&self, event: ReadyEvent);
+}
+
+impl<T: AsyncIoFd> AsyncIoFdDyn for T {
+    fn raw_fd(&self) -> RawFd {
+        self.as_raw_fd()
+    }
+
+    fn poll_ready(&self, cx: &mut Context<'_>, direction: Direction) -> Poll<ReadyEvent> {
+        sealed::Sealed::poll_ready(self, cx, direction)
+    }
+
+    fn clear_readiness(&self, event: ReadyEvent) {
+        sealed::Sealed::clear_readiness(self, event)
+    }
+}
+
+mod sealed {
+    use super::*;
+
+    pub trait Sealed {
+        fn poll_ready(&self, cx: &mut Context<'_>, direction: Direction) -> Poll<ReadyEvent>;
+        fn clear_readiness(&self, event: ReadyEvent);
+    }
+
+    macro_rules! impl_sealed {
+        ($($ty:ty),*) => {$(
+            impl Sealed for $ty {
+                fn poll_ready(&self, cx: &mut Context<'_>, direction: Direction) -> Poll<ReadyEvent> {
+                    self.io().poll_ready(cx, direction)
+                }
+
+                fn clear_readiness(&self, event: ReadyEvent) {
+                    self.io().clear_readiness(event)
+                }
+            }
+        )*};
+    }
+
+    impl_sealed!(TcpStream, UnixStream, Sender, Receiver);
+}
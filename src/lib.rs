@@ -1,14 +1,21 @@
+pub mod fs;
+pub mod io;
 pub mod net;
+pub mod process;
+pub mod signal;
+pub mod task;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub mod uring;
 
 mod reactor;
 mod rt;
 mod tp;
 
-pub use crate::rt::handle::JoinHandle;
+pub use crate::{rt::handle::JoinHandle, task::scope};
 
 use futures::Future;
 use reactor::Reactor;
-use rt::{builder::AsyncRuntimeBuilder, AsyncRuntime};
+use rt::{builder::AsyncRuntimeBuilder, AsyncRuntime, Header};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     OnceLock,
@@ -37,7 +44,7 @@ where
     T: Send + 'static,
     F: Future<Output = T> + Send + 'static,
 {
-    runtime().spawn_task(fut)
+    runtime().spawn_task(fut, Header::new(None, Default::default()))
 }
 
 impl AsyncRuntime {
@@ -61,8 +68,29 @@ fn runtime() -> &'static AsyncRuntime {
     RUNTIME.get().expect("runtime is not set")
 }
 
+/// Runtime if it's set and not terminated yet, for the callers which must not panic
+pub(crate) fn try_runtime() -> Option<&'static AsyncRuntime> {
+    if TERMINATED.load(Ordering::SeqCst) {
+        return None;
+    }
+
+    RUNTIME.get()
+}
+
 fn check_terminated() {
     if TERMINATED.load(Ordering::SeqCst) {
         panic!("runtime is already terminated");
     }
 }
+
+/// Register the global runtime shared by all tests of the crate
+#[cfg(test)]
+pub(crate) fn test_runtime() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        let builder = builder().worker_threads(4);
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        let builder = builder.io_uring(true);
+        builder.build().register();
+    });
+}
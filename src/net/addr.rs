@@ -0,0 +1,154 @@
+use crate::task::spawn_blocking;
+use std::{
+    future::Future,
+    io::{self, ErrorKind, Result},
+    net::{self, IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+};
+
+/// Values which can be resolved into one or more socket addresses.
+///
+/// Unlike the `std` trait, host names are resolved on the blocking pool, so the workers are not
+/// blocked by `getaddrinfo`. The trait is sealed.
+pub trait ToSocketAddrs: sealed::ToSocketAddrsPriv {}
+
+/// Resolve the host to the socket addresses on the blocking pool
+pub async fn lookup_host(host: impl ToSocketAddrs) -> Result<impl Iterator<Item = SocketAddr>> {
+    match host.to_socket_addrs() {
+        sealed::Resolve::Ready(addrs) => Ok(addrs.into_iter()),
+        sealed::Resolve::Lookup(host) => {
+            let addrs = spawn_blocking(move || net::ToSocketAddrs::to_socket_addrs(&host))
+                .await
+                .map_err(io::Error::other)??;
+
+            Ok(addrs.collect::<Vec<_>>().into_iter())
+        }
+    }
+}
+
+/// Call `f` with each resolved address until it succeeds, returning the last error otherwise
+pub(crate) async fn try_each_addr<T, F, Fut>(addr: impl ToSocketAddrs, mut f: F) -> Result<T>
+where
+    F: FnMut(SocketAddr) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut last_err = None;
+
+    for addr in lookup_host(addr).await? {
+        match f(addr).await {
+            Ok(res) => return Ok(res),
+            Err(e) => last_err = Some(e),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(ErrorKind::InvalidInput, "could not resolve to any address")
+    }))
+}
+
+impl<T: ToSocketAddrs + ?Sized> ToSocketAddrs for &T {}
+
+impl ToSocketAddrs for SocketAddr {}
+impl ToSocketAddrs for SocketAddrV4 {}
+impl ToSocketAddrs for SocketAddrV6 {}
+impl ToSocketAddrs for (IpAddr, u16) {}
+impl ToSocketAddrs for (Ipv4Addr, u16) {}
+impl ToSocketAddrs for (Ipv6Addr, u16) {}
+impl ToSocketAddrs for [SocketAddr] {}
+impl ToSocketAddrs for str {}
+impl ToSocketAddrs for String {}
+impl ToSocketAddrs for (&str, u16) {}
+impl ToSocketAddrs for (String, u16) {}
+
+pub(crate) mod sealed {
+    use super::*;
+
+    pub enum Resolve {
+        /// Address is known without the lookup
+        Ready(Vec<SocketAddr>),
+        /// `host:port` string to be resolved
+        Lookup(String),
+    }
+
+    pub trait ToSocketAddrsPriv {
+        fn to_socket_addrs(&self) -> Resolve;
+    }
+
+    impl<T: ToSocketAddrsPriv + ?Sized> ToSocketAddrsPriv for &T {
+        fn to_socket_addrs(&self) -> Resolve {
+            (**self).to_socket_addrs()
+        }
+    }
+
+    impl ToSocketAddrsPriv for SocketAddr {
+        fn to_socket_addrs(&self) -> Resolve {
+            Resolve::Ready(vec![*self])
+        }
+    }
+
+    impl ToSocketAddrsPriv for SocketAddrV4 {
+        fn to_socket_addrs(&self) -> Resolve {
+            SocketAddr::V4(*self).to_socket_addrs()
+        }
+    }
+
+    impl ToSocketAddrsPriv for SocketAddrV6 {
+        fn to_socket_addrs(&self) -> Resolve {
+            SocketAddr::V6(*self).to_socket_addrs()
+        }
+    }
+
+    impl ToSocketAddrsPriv for (IpAddr, u16) {
+        fn to_socket_addrs(&self) -> Resolve {
+            SocketAddr::from(*self).to_socket_addrs()
+        }
+    }
+
+    impl ToSocketAddrsPriv for (Ipv4Addr, u16) {
+        fn to_socket_addrs(&self) -> Resolve {
+            SocketAddr::from(*self).to_socket_addrs()
+        }
+    }
+
+    impl ToSocketAddrsPriv for (Ipv6Addr, u16) {
+        fn to_socket_addrs(&self) -> Resolve {
+            SocketAddr::from(*self).to_socket_addrs()
+        }
+    }
+
+    impl ToSocketAddrsPriv for [SocketAddr] {
+        fn to_socket_addrs(&self) -> Resolve {
+            Resolve::Ready(self.to_vec())
+        }
+    }
+
+    impl ToSocketAddrsPriv for str {
+        fn to_socket_addrs(&self) -> Resolve {
+            match self.parse::<SocketAddr>() {
+                Ok(addr) => addr.to_socket_addrs(),
+                Err(_) => Resolve::Lookup(self.to_owned()),
+            }
+        }
+    }
+
+    impl ToSocketAddrsPriv for String {
+        fn to_socket_addrs(&self) -> Resolve {
+            self.as_str().to_socket_addrs()
+        }
+    }
+
+    impl ToSocketAddrsPriv for (&str, u16) {
+        fn to_socket_addrs(&self) -> Resolve {
+            let (host, port) = *self;
+            match host.parse::<IpAddr>() {
+                Ok(ip) => (ip, port).to_socket_addrs(),
+                Err(_) => Resolve::Lookup(format!("{}:{}", host, port)),
+            }
+        }
+    }
+
+    impl ToSocketAddrsPriv for (String, u16) {
+        fn to_socket_addrs(&self) -> Resolve {
+            (self.0.as_str(), self.1).to_socket_addrs()
+        }
+    }
+}
@@ -1 +1,17 @@
+mod addr;
+mod split;
+
 pub mod tcp;
+pub mod udp;
+pub mod unix;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::{
+    addr::{lookup_host, ToSocketAddrs},
+    split::SplitStream,
+    tcp::{socket::TcpSocket, stream::TcpStream, TcpListener},
+    udp::UdpSocket,
+};
+pub use socket2::TcpKeepalive;
@@ -0,0 +1,221 @@
+use super::{unix::UnixStream, TcpStream};
+use futures::{AsyncRead, AsyncWrite};
+use std::{
+    io::Result,
+    net::Shutdown,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// Borrowed read half of the stream
+#[derive(Debug)]
+pub struct ReadHalf<'a, T>(&'a T);
+
+/// Borrowed write half of the stream
+#[derive(Debug)]
+pub struct WriteHalf<'a, T>(&'a T);
+
+pub(crate) fn split<T>(stream: &mut T) -> (ReadHalf<'_, T>, WriteHalf<'_, T>) {
+    (ReadHalf(stream), WriteHalf(stream))
+}
+
+/// Owned read half of the stream, which may be moved to another task
+#[derive(Debug)]
+pub struct OwnedReadHalf<T>(Arc<T>);
+
+/// Owned write half of the stream, which may be moved to another task.
+///
+/// The write half of the connection is shut down when it's dropped.
+#[derive(Debug)]
+pub struct OwnedWriteHalf<T: SplitStream> {
+    stream: Arc<T>,
+    shutdown_on_drop: bool,
+}
+
+pub(crate) fn into_split<T: SplitStream>(stream: T) -> (OwnedReadHalf<T>, OwnedWriteHalf<T>) {
+    let stream = Arc::new(stream);
+
+    (
+        OwnedReadHalf(Arc::clone(&stream)),
+        OwnedWriteHalf {
+            stream,
+            shutdown_on_drop: true,
+        },
+    )
+}
+
+/// Halves passed to `reunite` are not from the same stream
+#[derive(Debug, thiserror::Error)]
+#[error("tried to reunite halves that are not from the same stream")]
+pub struct ReuniteError<T: SplitStream>(pub OwnedReadHalf<T>, pub OwnedWriteHalf<T>);
+
+impl<T: SplitStream> OwnedReadHalf<T> {
+    /// Join the halves back into the stream
+    pub fn reunite(self, other: OwnedWriteHalf<T>) -> std::result::Result<T, ReuniteError<T>> {
+        reunite(self, other)
+    }
+
+    pub fn local_addr(&self) -> Result<T::Addr> {
+        self.0.local_addr()
+    }
+
+    pub fn peer_addr(&self) -> Result<T::Addr> {
+        self.0.peer_addr()
+    }
+}
+
+impl<T: SplitStream> OwnedWriteHalf<T> {
+    /// Join the halves back into the stream
+    pub fn reunite(self, other: OwnedReadHalf<T>) -> std::result::Result<T, ReuniteError<T>> {
+        reunite(other, self)
+    }
+
+    /// Drop the half without shutting down the write half of the connection
+    pub fn forget(mut self) {
+        self.shutdown_on_drop = false;
+    }
+
+    pub fn local_addr(&self) -> Result<T::Addr> {
+        self.stream.local_addr()
+    }
+
+    pub fn peer_addr(&self) -> Result<T::Addr> {
+        self.stream.peer_addr()
+    }
+}
+
+fn reunite<T: SplitStream>(
+    read: OwnedReadHalf<T>,
+    mut write: OwnedWriteHalf<T>,
+) -> std::result::Result<T, ReuniteError<T>> {
+    if !Arc::ptr_eq(&read.0, &write.stream) {
+        return Err(ReuniteError(read, write));
+    }
+
+    write.shutdown_on_drop = false;
+    drop(write);
+
+    match Arc::try_unwrap(read.0) {
+        Ok(stream) => Ok(stream),
+        Err(_) => unreachable!("stream is shared only between its halves"),
+    }
+}
+
+impl<T: SplitStream> Drop for OwnedWriteHalf<T> {
+    fn drop(&mut self) {
+        if self.shutdown_on_drop {
+            self.stream.shutdown(Shutdown::Write).ok();
+        }
+    }
+}
+
+impl<T: SplitStream> AsyncRead for ReadHalf<'_, T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.0.poll_read_priv(cx, buf)
+    }
+}
+
+impl<T: SplitStream> AsyncRead for OwnedReadHalf<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.0.poll_read_priv(cx, buf)
+    }
+}
+
+impl<T: SplitStream> AsyncWrite for WriteHalf<'_, T> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.0.poll_write_priv(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.0.poll_flush_priv(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(self.0.shutdown(Shutdown::Write))
+    }
+}
+
+impl<T: SplitStream> AsyncWrite for OwnedWriteHalf<T> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.stream.poll_write_priv(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.stream.poll_flush_priv(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(self.stream.shutdown(Shutdown::Write))
+    }
+}
+
+/// Stream which can be split into the read and write halves
+pub trait SplitStream: sealed::Sealed {}
+
+impl SplitStream for TcpStream {}
+impl SplitStream for UnixStream {}
+
+mod sealed {
+    use super::*;
+
+    pub trait Sealed {
+        type Addr;
+
+        fn poll_read_priv(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>>;
+        fn poll_write_priv(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>>;
+        fn poll_flush_priv(&self, cx: &mut Context<'_>) -> Poll<Result<()>>;
+        fn shutdown(&self, how: Shutdown) -> Result<()>;
+        fn local_addr(&self) -> Result<Self::Addr>;
+        fn peer_addr(&self) -> Result<Self::Addr>;
+    }
+
+    macro_rules! impl_sealed {
+        ($($ty:ty => $addr:ty),*) => {$(
+            impl Sealed for $ty {
+                type Addr = $addr;
+
+                fn poll_read_priv(
+                    &self,
+                    cx: &mut Context<'_>,
+                    buf: &mut [u8],
+                ) -> Poll<Result<usize>> {
+                    self.io().poll_read(cx, buf)
+                }
+
+                fn poll_write_priv(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+                    self.io().poll_write(cx, buf)
+                }
+
+                fn poll_flush_priv(&self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                    self.io().poll_flush(cx)
+                }
+
+                fn shutdown(&self, how: Shutdown) -> Result<()> {
+                    <$ty>::shutdown(self, how)
+                }
+
+                fn local_addr(&self) -> Result<Self::Addr> {
+                    <$ty>::local_addr(self)
+                }
+
+                fn peer_addr(&self) -> Result<Self::Addr> {
+                    <$ty>::peer_addr(self)
+                }
+            }
+        )*};
+    }
+
+    impl_sealed!(
+        TcpStream => std::net::SocketAddr,
+        UnixStream => crate::net::unix::SocketAddr
+    );
+}
@@ -1,48 +1,114 @@
+pub mod socket;
+pub mod split;
 pub mod stream;
 
-use crate::reactor::io_handle::IoHandle;
+#[cfg(test)]
+mod tests;
+
+use crate::net::{addr, ToSocketAddrs};
+use crate::reactor::{io_handle::IoHandle, scheduled_io::Direction};
 use futures::Stream;
 use mio::{net::TcpListener as MioTcpListener, Interest};
 use std::{
+    future::{self, poll_fn},
     io,
-    net::SocketAddr,
+    net::{self, SocketAddr},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, RawFd},
     pin::Pin,
-    task::{Context, Poll},
+    task::{ready, Context, Poll},
 };
 
 use self::stream::TcpStream;
 
-pub struct TcpListener(MioTcpListener);
+pub struct TcpListener(IoHandle<MioTcpListener>);
 
 impl TcpListener {
-    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
-        Ok(Self(MioTcpListener::bind(addr)?))
+    /// Bind the listener to the address. If the address resolves to multiple socket addresses,
+    /// each of them is tried in turn until one succeeds.
+    pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        addr::try_each_addr(addr, |addr| {
+            future::ready(MioTcpListener::bind(addr).and_then(Self::new))
+        })
+        .await
+    }
+
+    /// Create the listener from the standard library one. The listener is switched
+    /// to the non-blocking mode.
+    pub fn from_std(listener: net::TcpListener) -> io::Result<Self> {
+        listener.set_nonblocking(true)?;
+        Self::new(MioTcpListener::from_std(listener))
+    }
+
+    /// Deregister the listener from the runtime and turn it into the standard library one.
+    /// The listener stays in the non-blocking mode.
+    pub fn into_std(self) -> io::Result<net::TcpListener> {
+        let listener = self.0.into_inner()?;
+        // SAFETY: the descriptor is owned by the listener which is consumed
+        Ok(unsafe { net::TcpListener::from_raw_fd(listener.into_raw_fd()) })
+    }
+
+    pub(crate) fn new(listener: MioTcpListener) -> io::Result<Self> {
+        Ok(Self(IoHandle::with_interest(listener, Interest::READABLE)?))
+    }
+
+    /// Accept the new incoming connection.
+    ///
+    /// The listener may be shared between the several tasks accepting the connections
+    /// concurrently.
+    pub async fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        poll_fn(|cx| self.poll_accept(cx)).await
+    }
+
+    pub fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<(TcpStream, SocketAddr)>> {
+        let (stream, addr) = ready!(self.0.poll_io(cx, Direction::Read, MioTcpListener::accept))?;
+
+        Poll::Ready(Ok((TcpStream::new(stream)?, addr)))
     }
 
-    pub fn accept(self) -> Accept {
-        self.0.into()
+    /// Stream of the incoming connections
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming(self)
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.source().local_addr()
+    }
+
+    /// Set the value for the `IP_TTL` option on this socket
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.0.source().set_ttl(ttl)
+    }
+
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.0.source().ttl()
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.0.source().take_error()
     }
 }
 
-pub struct Accept(IoHandle<MioTcpListener>);
+impl AsRawFd for TcpListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.source().as_raw_fd()
+    }
+}
 
-impl From<MioTcpListener> for Accept {
-    fn from(source: MioTcpListener) -> Self {
-        Self(IoHandle::new(source))
+impl AsFd for TcpListener {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the descriptor is owned by the listener and lives as long as it does
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
     }
 }
 
-impl Stream for Accept {
+/// Stream of the connections accepted by the `TcpListener`
+pub struct Incoming<'a>(&'a TcpListener);
+
+impl Stream for Incoming<'_> {
     type Item = io::Result<(TcpStream, SocketAddr)>;
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match self.0.source().accept() {
-            Ok((stream, addr)) => Poll::Ready(Some(Ok((stream.into(), addr)))),
-            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                self.0.register(Interest::READABLE, cx.waker().clone())?;
-                Poll::Pending
-            }
-            Err(e) => Poll::Ready(Some(Err(e))),
-        }
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.poll_accept(cx).map(Some)
     }
 }
@@ -0,0 +1,98 @@
+use super::{stream::TcpStream, TcpListener};
+use mio::net::{TcpListener as MioTcpListener, TcpStream as MioTcpStream};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::{
+    io::{ErrorKind, Result},
+    net::SocketAddr,
+};
+
+/// TCP socket which is not yet turned into the `TcpStream` or `TcpListener`.
+///
+/// Use it to set the socket options which must be applied before binding or connecting.
+pub struct TcpSocket(Socket);
+
+impl TcpSocket {
+    pub fn new_v4() -> Result<Self> {
+        Self::new(Domain::IPV4)
+    }
+
+    pub fn new_v6() -> Result<Self> {
+        Self::new(Domain::IPV6)
+    }
+
+    /// Create the socket of the same family as the address
+    pub fn new_for_addr(addr: SocketAddr) -> Result<Self> {
+        Self::new(Domain::for_address(addr))
+    }
+
+    fn new(domain: Domain) -> Result<Self> {
+        let socket = Socket::new(domain, Type::STREAM, Some(Protocol::TCP))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self(socket))
+    }
+
+    /// Allow the socket to bind to the address in use (`SO_REUSEADDR`)
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> Result<()> {
+        self.0.set_reuse_address(reuseaddr)
+    }
+
+    pub fn reuseaddr(&self) -> Result<bool> {
+        self.0.reuse_address()
+    }
+
+    /// Allow the several sockets to bind to the same port (`SO_REUSEPORT`)
+    pub fn set_reuseport(&self, reuseport: bool) -> Result<()> {
+        self.0.set_reuse_port(reuseport)
+    }
+
+    pub fn reuseport(&self) -> Result<bool> {
+        self.0.reuse_port()
+    }
+
+    /// Set the size of the send buffer (`SO_SNDBUF`)
+    pub fn set_send_buffer_size(&self, size: u32) -> Result<()> {
+        self.0.set_send_buffer_size(size as usize)
+    }
+
+    pub fn send_buffer_size(&self) -> Result<u32> {
+        self.0.send_buffer_size().map(|size| size as u32)
+    }
+
+    /// Set the size of the receive buffer (`SO_RCVBUF`)
+    pub fn set_recv_buffer_size(&self, size: u32) -> Result<()> {
+        self.0.set_recv_buffer_size(size as usize)
+    }
+
+    pub fn recv_buffer_size(&self) -> Result<u32> {
+        self.0.recv_buffer_size().map(|size| size as u32)
+    }
+
+    pub fn bind(&self, addr: SocketAddr) -> Result<()> {
+        self.0.bind(&addr.into())
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.0
+            .local_addr()?
+            .as_socket()
+            .ok_or_else(|| ErrorKind::Unsupported.into())
+    }
+
+    /// Turn the socket into the listener
+    pub fn listen(self, backlog: u32) -> Result<TcpListener> {
+        self.0.listen(backlog.try_into().unwrap_or(i32::MAX))?;
+        TcpListener::new(MioTcpListener::from_std(self.0.into()))
+    }
+
+    /// Connect the socket to the remote host
+    pub async fn connect(self, addr: SocketAddr) -> Result<TcpStream> {
+        match self.0.connect(&addr.into()) {
+            Ok(()) => {}
+            // The connection is established in background
+            Err(ref e) if e.raw_os_error() == Some(libc::EINPROGRESS) => {}
+            Err(e) => return Err(e),
+        }
+
+        TcpStream::connect_mio(MioTcpStream::from_std(self.0.into())).await
+    }
+}
@@ -0,0 +1,21 @@
+use super::stream::TcpStream;
+use crate::net::split;
+
+pub(super) use crate::net::split::{into_split, split};
+
+/// Borrowed read half of the `TcpStream`
+pub type ReadHalf<'a> = split::ReadHalf<'a, TcpStream>;
+
+/// Borrowed write half of the `TcpStream`
+pub type WriteHalf<'a> = split::WriteHalf<'a, TcpStream>;
+
+/// Owned read half of the `TcpStream`, which may be moved to another task
+pub type OwnedReadHalf = split::OwnedReadHalf<TcpStream>;
+
+/// Owned write half of the `TcpStream`, which may be moved to another task.
+///
+/// The write half of the connection is shut down when it's dropped.
+pub type OwnedWriteHalf = split::OwnedWriteHalf<TcpStream>;
+
+/// Halves passed to `reunite` are not from the same stream
+pub type ReuniteError = split::ReuniteError<TcpStream>;
@@ -1,45 +1,277 @@
-use crate::reactor::io_handle::IoHandle;
+use super::split::{self, OwnedReadHalf, OwnedWriteHalf, ReadHalf, WriteHalf};
+use crate::net::{addr, ToSocketAddrs};
+use crate::reactor::{io_handle::IoHandle, scheduled_io::Direction};
 use futures::{AsyncRead, AsyncWrite};
 use mio::net::TcpStream as MioTcpStream;
+use socket2::{SockRef, TcpKeepalive};
 use std::{
-    io::Result,
-    net::Shutdown,
+    fmt,
+    io::{self, ErrorKind, IoSlice, IoSliceMut, Read, Result, Write},
+    net::{self, Shutdown, SocketAddr},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, RawFd},
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 
 pub struct TcpStream(IoHandle<MioTcpStream>);
 
-impl From<MioTcpStream> for TcpStream {
-    fn from(stream: MioTcpStream) -> Self {
-        Self(IoHandle::new(stream))
+impl TcpStream {
+    /// Open a TCP connection to the remote host.
+    ///
+    /// If the address resolves to multiple socket addresses, each of them is tried in turn
+    /// until the connection succeeds. The error of the last attempt is returned otherwise.
+    pub async fn connect(addr: impl ToSocketAddrs) -> Result<Self> {
+        addr::try_each_addr(addr, Self::connect_addr).await
+    }
+
+    async fn connect_addr(addr: SocketAddr) -> Result<Self> {
+        // Non-blocking connect returns immediately, the connection is established in background
+        Self::connect_mio(MioTcpStream::connect(addr)?).await
+    }
+
+    /// Wait for the connection initiated on the non-blocking socket
+    pub(crate) async fn connect_mio(stream: MioTcpStream) -> Result<Self> {
+        let handle = IoHandle::new(stream)?;
+
+        loop {
+            // The socket becomes writable when the connection is either established or failed
+            let event = handle.ready(Direction::Write).await;
+
+            if let Some(e) = handle.source().take_error()? {
+                return Err(e);
+            }
+
+            match handle.source().peer_addr() {
+                Ok(_) => return Ok(Self(handle)),
+                // Spurious wakeup, the connection is still in progress
+                Err(ref e) if e.kind() == ErrorKind::NotConnected => handle.clear_readiness(event),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Create the stream from the standard library one. The stream is switched
+    /// to the non-blocking mode.
+    pub fn from_std(stream: net::TcpStream) -> Result<Self> {
+        stream.set_nonblocking(true)?;
+        Self::new(MioTcpStream::from_std(stream))
+    }
+
+    /// Deregister the stream from the runtime and turn it into the standard library one.
+    /// The stream stays in the non-blocking mode.
+    pub fn into_std(self) -> Result<net::TcpStream> {
+        let stream = self.0.into_inner()?;
+        // SAFETY: the descriptor is owned by the stream which is consumed
+        Ok(unsafe { net::TcpStream::from_raw_fd(stream.into_raw_fd()) })
+    }
+
+    pub(crate) fn new(stream: MioTcpStream) -> Result<Self> {
+        Ok(Self(IoHandle::new(stream)?))
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.0.source().local_addr()
+    }
+
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.0.source().peer_addr()
+    }
+
+    /// Set the value of the `TCP_NODELAY` option on this socket
+    pub fn set_nodelay(&self, nodelay: bool) -> Result<()> {
+        self.0.source().set_nodelay(nodelay)
+    }
+
+    pub fn nodelay(&self) -> Result<bool> {
+        self.0.source().nodelay()
+    }
+
+    /// Set the value for the `IP_TTL` option on this socket
+    pub fn set_ttl(&self, ttl: u32) -> Result<()> {
+        self.0.source().set_ttl(ttl)
+    }
+
+    pub fn ttl(&self) -> Result<u32> {
+        self.0.source().ttl()
+    }
+
+    /// Set the value of the `SO_LINGER` option on this socket. `None` disables lingering.
+    pub fn set_linger(&self, linger: Option<Duration>) -> Result<()> {
+        self.sock_ref().set_linger(linger)
+    }
+
+    pub fn linger(&self) -> Result<Option<Duration>> {
+        self.sock_ref().linger()
+    }
+
+    /// Enable or disable the `SO_KEEPALIVE` option with the system default parameters
+    pub fn set_keepalive(&self, keepalive: bool) -> Result<()> {
+        self.sock_ref().set_keepalive(keepalive)
+    }
+
+    pub fn keepalive(&self) -> Result<bool> {
+        self.sock_ref().keepalive()
+    }
+
+    /// Enable the `SO_KEEPALIVE` option with the given idle time, probes interval and count
+    pub fn set_tcp_keepalive(&self, params: &TcpKeepalive) -> Result<()> {
+        self.sock_ref().set_tcp_keepalive(params)
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> Result<Option<io::Error>> {
+        self.0.source().take_error()
+    }
+
+    /// Shut down the read, write, or both halves of the connection.
+    ///
+    /// `Shutdown::Write` is a half-close: the peer receives EOF, but the data sent by the peer
+    /// still may be read.
+    pub fn shutdown(&self, how: Shutdown) -> Result<()> {
+        self.0.source().shutdown(how)
+    }
+
+    /// Receive the data without removing it from the queue
+    pub async fn peek(&self, buf: &mut [u8]) -> Result<usize> {
+        self.0
+            .async_io(Direction::Read, |stream| stream.peek(buf))
+            .await
+    }
+
+    pub fn poll_peek(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.0
+            .poll_io(cx, Direction::Read, |stream| stream.peek(buf))
+    }
+
+    /// Wait until the stream becomes readable. The readiness may be spurious, so the following
+    /// `try_read` may still return `WouldBlock`.
+    pub async fn readable(&self) -> Result<()> {
+        self.0.ready(Direction::Read).await;
+        Ok(())
+    }
+
+    /// Wait until the stream becomes writable. The readiness may be spurious, so the following
+    /// `try_write` may still return `WouldBlock`.
+    pub async fn writable(&self) -> Result<()> {
+        self.0.ready(Direction::Write).await;
+        Ok(())
+    }
+
+    pub fn poll_read_ready(&self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.0.poll_ready(cx, Direction::Read).map(|_| Ok(()))
+    }
+
+    pub fn poll_write_ready(&self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.0.poll_ready(cx, Direction::Write).map(|_| Ok(()))
+    }
+
+    /// Try to read the data without waiting. Returns `WouldBlock` if the stream is not ready,
+    /// in this case the readiness is cleared and `readable` waits for the next event.
+    pub fn try_read(&self, buf: &mut [u8]) -> Result<usize> {
+        self.0
+            .try_io(Direction::Read, |mut stream| stream.read(buf))
+    }
+
+    pub fn try_read_vectored(&self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize> {
+        self.0
+            .try_io(Direction::Read, |mut stream| stream.read_vectored(bufs))
+    }
+
+    /// Try to write the data without waiting. Returns `WouldBlock` if the stream is not ready,
+    /// in this case the readiness is cleared and `writable` waits for the next event.
+    pub fn try_write(&self, buf: &[u8]) -> Result<usize> {
+        self.0
+            .try_io(Direction::Write, |mut stream| stream.write(buf))
+    }
+
+    pub fn try_write_vectored(&self, bufs: &[IoSlice<'_>]) -> Result<usize> {
+        self.0
+            .try_io(Direction::Write, |mut stream| stream.write_vectored(bufs))
+    }
+
+    /// Whether the stream has an efficient `poll_write_vectored` implementation.
+    /// Always `true`, the buffers are written with the single `writev` call.
+    pub fn is_write_vectored(&self) -> bool {
+        true
+    }
+
+    /// Split the stream into the read and write halves borrowing it, so both directions may be
+    /// used concurrently within the task
+    pub fn split(&mut self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        split::split(self)
+    }
+
+    /// Split the stream into the owned read and write halves, which may be moved to different
+    /// tasks. The halves can be joined back with `reunite`.
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        split::into_split(self)
+    }
+
+    pub(crate) fn io(&self) -> &IoHandle<MioTcpStream> {
+        &self.0
+    }
+
+    fn sock_ref(&self) -> SockRef<'_> {
+        SockRef::from(self)
+    }
+}
+
+impl fmt::Debug for TcpStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.source().fmt(f)
+    }
+}
+
+impl AsRawFd for TcpStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.source().as_raw_fd()
     }
 }
 
+impl AsFd for TcpStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the descriptor is owned by the stream and lives as long as it does
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
 impl AsyncRead for TcpStream {
     fn poll_read(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<Result<usize>> {
-        Pin::new(&mut self.0).poll_read(cx, buf)
+        self.0.poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<Result<usize>> {
+        self.0.poll_read_vectored(cx, bufs)
     }
 }
 
 impl AsyncWrite for TcpStream {
-    fn poll_write(
-        mut self: Pin<&mut Self>,
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.0.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
-        buf: &[u8],
+        bufs: &[IoSlice<'_>],
     ) -> Poll<Result<usize>> {
-        Pin::new(&mut self.0).poll_write(cx, buf)
+        self.0.poll_write_vectored(cx, bufs)
     }
 
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        Pin::new(&mut self.0).poll_flush(cx)
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.0.poll_flush(cx)
     }
 
+    /// Shut down the write half of the connection, the read half stays open
     fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
-        Poll::Ready(self.0.source().shutdown(Shutdown::Both))
+        Poll::Ready(self.shutdown(Shutdown::Write))
     }
 }
@@ -0,0 +1,231 @@
+use crate::net::{TcpKeepalive, TcpListener, TcpSocket, TcpStream};
+use futures::{executor::block_on, AsyncReadExt, AsyncWriteExt, StreamExt};
+use std::{
+    io::{ErrorKind, IoSlice},
+    sync::Arc,
+    time::Duration,
+};
+
+#[test]
+fn test_connect_accept() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = crate::spawn(async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_all(b"ping").await.unwrap();
+
+            let mut buf = [0; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            buf
+        });
+
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        stream.write_all(b"pong").await.unwrap();
+
+        (buf, client.await.unwrap())
+    });
+
+    assert_eq!(block_on(handle).unwrap(), (*b"ping", *b"pong"));
+}
+
+#[test]
+fn test_shared_listener() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let listener = Arc::new(TcpListener::bind("127.0.0.1:0").await.unwrap());
+        let addr = listener.local_addr().unwrap();
+
+        let acceptors = (0..2)
+            .map(|_| Arc::clone(&listener))
+            .map(|listener| crate::spawn(async move { listener.incoming().next().await }))
+            .collect::<Vec<_>>();
+
+        let _clients = (
+            TcpStream::connect(addr).await.unwrap(),
+            TcpStream::connect(addr).await.unwrap(),
+        );
+
+        for acceptor in acceptors {
+            acceptor.await.unwrap().unwrap().unwrap();
+        }
+
+        listener.local_addr().unwrap() == addr
+    });
+
+    assert!(block_on(handle).unwrap());
+}
+
+#[test]
+fn test_socket() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let socket = TcpSocket::new_v4().unwrap();
+        socket.set_reuseport(true).unwrap();
+        socket.set_recv_buffer_size(64 * 1024).unwrap();
+        socket.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+
+        assert!(socket.reuseport().unwrap());
+
+        let listener = socket.listen(16).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = TcpSocket::new_v4().unwrap();
+        client.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let client_addr = client.local_addr().unwrap();
+
+        let _stream = client.connect(addr).await.unwrap();
+        let (_, peer_addr) = listener.accept().await.unwrap();
+
+        peer_addr == client_addr
+    });
+
+    assert!(block_on(handle).unwrap());
+}
+
+#[test]
+fn test_half_close() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+
+        client.set_nodelay(true).unwrap();
+        client
+            .set_tcp_keepalive(&TcpKeepalive::new().with_time(Duration::from_secs(30)))
+            .unwrap();
+        assert!(client.nodelay().unwrap() && client.keepalive().unwrap());
+        assert_eq!(client.local_addr().unwrap(), server.peer_addr().unwrap());
+
+        // Client sends the request and closes the write half, but still reads the response
+        client.write_all(b"request").await.unwrap();
+        client.close().await.unwrap();
+
+        let mut request = Vec::new();
+        server.read_to_end(&mut request).await.unwrap();
+        server.write_all(b"response").await.unwrap();
+        drop(server);
+
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+
+        (request, response)
+    });
+
+    let (request, response) = block_on(handle).unwrap();
+    assert_eq!(
+        (&request[..], &response[..]),
+        (&b"request"[..], &b"response"[..])
+    );
+}
+
+#[test]
+fn test_into_split() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        // Echo server: one task reads while another one writes the same connection
+        let (mut server_read, mut server_write) = server.into_split();
+        let echo = crate::spawn(async move {
+            futures::io::copy(&mut server_read, &mut server_write)
+                .await
+                .unwrap();
+        });
+
+        let (mut client_read, mut client_write) = client.into_split();
+        let reader = crate::spawn(async move {
+            let mut buf = Vec::new();
+            client_read.read_to_end(&mut buf).await.unwrap();
+            (buf, client_read)
+        });
+
+        client_write.write_all(b"hello, ").await.unwrap();
+        client_write.write_all(b"world").await.unwrap();
+        client_write.close().await.unwrap();
+
+        let (buf, client_read) = reader.await.unwrap();
+        echo.await.unwrap();
+
+        (buf, client_read.reunite(client_write).is_ok())
+    });
+
+    let (buf, reunited) = block_on(handle).unwrap();
+    assert_eq!((&buf[..], reunited), (&b"hello, world"[..], true));
+}
+
+#[test]
+fn test_readiness() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+
+        let bufs = [IoSlice::new(b"hello, "), IoSlice::new(b"world")];
+        let written = loop {
+            client.writable().await.unwrap();
+            match client.try_write_vectored(&bufs) {
+                Err(e) if e.kind() == ErrorKind::WouldBlock => continue,
+                res => break res.unwrap(),
+            }
+        };
+
+        let mut peeked = [0; 5];
+        let n = server.peek(&mut peeked).await.unwrap();
+
+        let mut buf = [0; 12];
+        server.read_exact(&mut buf).await.unwrap();
+
+        (written, peeked[..n].to_vec(), buf)
+    });
+
+    let (written, peeked, buf) = block_on(handle).unwrap();
+    assert_eq!(written, 12);
+    assert!(b"hello".starts_with(&peeked) && !peeked.is_empty());
+    assert_eq!(&buf, b"hello, world");
+}
+
+#[test]
+fn test_std_conversion() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let std = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+
+        let mut client = TcpStream::from_std(std).unwrap();
+        client.write_all(b"ping").await.unwrap();
+
+        let mut buf = [0; 4];
+        server.read_exact(&mut buf).await.unwrap();
+
+        (
+            buf,
+            client.into_std().unwrap().local_addr().unwrap() == server.peer_addr().unwrap(),
+        )
+    });
+
+    assert_eq!(block_on(handle).unwrap(), (*b"ping", true));
+}
@@ -0,0 +1,53 @@
+use crate::net::{lookup_host, TcpListener, TcpStream, UdpSocket};
+use futures::executor::block_on;
+use std::net::SocketAddr;
+
+#[test]
+fn test_udp() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let server_addr = server.local_addr().unwrap();
+
+        client.connect(server_addr).await.unwrap();
+        client.send(b"query").await.unwrap();
+
+        let mut buf = [0; 16];
+        let (peeked, _) = server.peek_from(&mut buf).await.unwrap();
+        let (n, client_addr) = server.recv_from(&mut buf).await.unwrap();
+        assert_eq!((peeked, &buf[..n]), (5, &b"query"[..]));
+
+        server.send_to(b"answer", client_addr).await.unwrap();
+
+        let n = client.recv(&mut buf).await.unwrap();
+        buf[..n].to_vec()
+    });
+
+    assert_eq!(block_on(handle).unwrap(), b"answer");
+}
+
+#[test]
+fn test_lookup_host() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let literal: Vec<_> = lookup_host("127.0.0.1:80").await.unwrap().collect();
+        let resolved: Vec<_> = lookup_host(("localhost", 80)).await.unwrap().collect();
+
+        // Host name is accepted by `bind` and `connect` as well
+        let listener = TcpListener::bind("localhost:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        TcpStream::connect(("localhost", port)).await.unwrap();
+
+        (literal, resolved)
+    });
+
+    let (literal, resolved) = block_on(handle).unwrap();
+    assert_eq!(literal, ["127.0.0.1:80".parse::<SocketAddr>().unwrap()]);
+    assert!(resolved
+        .iter()
+        .all(|addr| addr.ip().is_loopback() && addr.port() == 80));
+    assert!(!resolved.is_empty());
+}
@@ -0,0 +1,224 @@
+use crate::net::{addr, lookup_host, ToSocketAddrs};
+use crate::reactor::{io_handle::IoHandle, scheduled_io::Direction};
+use mio::net::UdpSocket as MioUdpSocket;
+use std::{
+    fmt, future,
+    io::{self, ErrorKind, Result},
+    net::{self, Ipv4Addr, Ipv6Addr, SocketAddr},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, RawFd},
+    task::{Context, Poll},
+};
+
+/// UDP socket.
+///
+/// All methods take `&self`, so the socket may be shared between the tasks, e.g. one task
+/// receives the datagrams while another one sends them.
+pub struct UdpSocket(IoHandle<MioUdpSocket>);
+
+impl UdpSocket {
+    /// Bind the socket to the address. If the address resolves to multiple socket addresses,
+    /// each of them is tried in turn until one succeeds.
+    pub async fn bind(addr: impl ToSocketAddrs) -> Result<Self> {
+        addr::try_each_addr(addr, |addr| {
+            future::ready(MioUdpSocket::bind(addr).and_then(Self::new))
+        })
+        .await
+    }
+
+    /// Create the socket from the standard library one. The socket is switched
+    /// to the non-blocking mode.
+    pub fn from_std(socket: net::UdpSocket) -> Result<Self> {
+        socket.set_nonblocking(true)?;
+        Self::new(MioUdpSocket::from_std(socket))
+    }
+
+    /// Deregister the socket from the runtime and turn it into the standard library one.
+    /// The socket stays in the non-blocking mode.
+    pub fn into_std(self) -> Result<net::UdpSocket> {
+        let socket = self.0.into_inner()?;
+        // SAFETY: the descriptor is owned by the socket which is consumed
+        Ok(unsafe { net::UdpSocket::from_raw_fd(socket.into_raw_fd()) })
+    }
+
+    fn new(socket: MioUdpSocket) -> Result<Self> {
+        Ok(Self(IoHandle::new(socket)?))
+    }
+
+    /// Set the default destination for `send` and limit `recv` to the datagrams from this
+    /// address
+    pub async fn connect(&self, addr: impl ToSocketAddrs) -> Result<()> {
+        addr::try_each_addr(addr, |addr| future::ready(self.0.source().connect(addr))).await
+    }
+
+    /// Send the datagram to the connected peer
+    pub async fn send(&self, buf: &[u8]) -> Result<usize> {
+        self.0
+            .async_io(Direction::Write, |socket| socket.send(buf))
+            .await
+    }
+
+    pub fn poll_send(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.0
+            .poll_io(cx, Direction::Write, |socket| socket.send(buf))
+    }
+
+    /// Receive the datagram from the connected peer
+    pub async fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        self.0
+            .async_io(Direction::Read, |socket| socket.recv(buf))
+            .await
+    }
+
+    pub fn poll_recv(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.0
+            .poll_io(cx, Direction::Read, |socket| socket.recv(buf))
+    }
+
+    /// Send the datagram to the target. If the target resolves to multiple socket addresses,
+    /// the first one is used.
+    pub async fn send_to(&self, buf: &[u8], target: impl ToSocketAddrs) -> Result<usize> {
+        let target = lookup_host(target).await?.next().ok_or_else(|| {
+            io::Error::new(ErrorKind::InvalidInput, "could not resolve to any address")
+        })?;
+
+        self.0
+            .async_io(Direction::Write, |socket| socket.send_to(buf, target))
+            .await
+    }
+
+    pub fn poll_send_to(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        target: SocketAddr,
+    ) -> Poll<Result<usize>> {
+        self.0
+            .poll_io(cx, Direction::Write, |socket| socket.send_to(buf, target))
+    }
+
+    pub async fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr)> {
+        self.0
+            .async_io(Direction::Read, |socket| socket.recv_from(buf))
+            .await
+    }
+
+    pub fn poll_recv_from(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<(usize, SocketAddr)>> {
+        self.0
+            .poll_io(cx, Direction::Read, |socket| socket.recv_from(buf))
+    }
+
+    /// Receive the datagram without removing it from the queue
+    pub async fn peek_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr)> {
+        self.0
+            .async_io(Direction::Read, |socket| socket.peek_from(buf))
+            .await
+    }
+
+    pub fn poll_peek_from(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<(usize, SocketAddr)>> {
+        self.0
+            .poll_io(cx, Direction::Read, |socket| socket.peek_from(buf))
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.0.source().local_addr()
+    }
+
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.0.source().peer_addr()
+    }
+
+    /// Set the value of the `SO_BROADCAST` option on this socket
+    pub fn set_broadcast(&self, on: bool) -> Result<()> {
+        self.0.source().set_broadcast(on)
+    }
+
+    pub fn broadcast(&self) -> Result<bool> {
+        self.0.source().broadcast()
+    }
+
+    /// Set the value for the `IP_TTL` option on this socket
+    pub fn set_ttl(&self, ttl: u32) -> Result<()> {
+        self.0.source().set_ttl(ttl)
+    }
+
+    pub fn ttl(&self) -> Result<u32> {
+        self.0.source().ttl()
+    }
+
+    /// Join the IPv4 multicast group on the given interface
+    pub fn join_multicast_v4(&self, multiaddr: Ipv4Addr, interface: Ipv4Addr) -> Result<()> {
+        self.0.source().join_multicast_v4(&multiaddr, &interface)
+    }
+
+    /// Join the IPv6 multicast group on the interface with the given index
+    pub fn join_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> Result<()> {
+        self.0.source().join_multicast_v6(multiaddr, interface)
+    }
+
+    pub fn leave_multicast_v4(&self, multiaddr: Ipv4Addr, interface: Ipv4Addr) -> Result<()> {
+        self.0.source().leave_multicast_v4(&multiaddr, &interface)
+    }
+
+    pub fn leave_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> Result<()> {
+        self.0.source().leave_multicast_v6(multiaddr, interface)
+    }
+
+    /// Set the value of the `IP_MULTICAST_LOOP` option on this socket
+    pub fn set_multicast_loop_v4(&self, on: bool) -> Result<()> {
+        self.0.source().set_multicast_loop_v4(on)
+    }
+
+    pub fn multicast_loop_v4(&self) -> Result<bool> {
+        self.0.source().multicast_loop_v4()
+    }
+
+    /// Set the value of the `IP_MULTICAST_TTL` option on this socket
+    pub fn set_multicast_ttl_v4(&self, ttl: u32) -> Result<()> {
+        self.0.source().set_multicast_ttl_v4(ttl)
+    }
+
+    pub fn multicast_ttl_v4(&self) -> Result<u32> {
+        self.0.source().multicast_ttl_v4()
+    }
+
+    /// Set the value of the `IPV6_MULTICAST_LOOP` option on this socket
+    pub fn set_multicast_loop_v6(&self, on: bool) -> Result<()> {
+        self.0.source().set_multicast_loop_v6(on)
+    }
+
+    pub fn multicast_loop_v6(&self) -> Result<bool> {
+        self.0.source().multicast_loop_v6()
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> Result<Option<std::io::Error>> {
+        self.0.source().take_error()
+    }
+}
+
+impl fmt::Debug for UdpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.source().fmt(f)
+    }
+}
+
+impl AsRawFd for UdpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.source().as_raw_fd()
+    }
+}
+
+impl AsFd for UdpSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the descriptor is owned by the socket and lives as long as it does
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    io::{Error, Result},
+    mem,
+    os::fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd},
+    ptr,
+};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const RECV_FLAGS: libc::c_int = libc::MSG_CMSG_CLOEXEC;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const RECV_FLAGS: libc::c_int = 0;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SEND_FLAGS: libc::c_int = libc::MSG_NOSIGNAL;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const SEND_FLAGS: libc::c_int = 0;
+
+/// Buffer for the control messages. It's made of `u64` to be aligned for `cmsghdr`.
+fn control_buf(fds: usize) -> (Vec<u64>, usize) {
+    // SAFETY: `CMSG_SPACE` is a pure computation
+    let space = unsafe { libc::CMSG_SPACE((fds * mem::size_of::<RawFd>()) as u32) } as usize;
+    (vec![0; space.div_ceil(mem::size_of::<u64>())], space)
+}
+
+/// Send the data with the file descriptors attached as `SCM_RIGHTS` control message
+pub(crate) fn send_with_fds(socket: RawFd, buf: &[u8], fds: &[BorrowedFd<'_>]) -> Result<usize> {
+    let mut iov = libc::iovec {
+        iov_base: buf.as_ptr() as *mut _,
+        iov_len: buf.len(),
+    };
+
+    // SAFETY: all-zero `msghdr` is valid
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+
+    let (mut control, space) = control_buf(fds.len());
+
+    if !fds.is_empty() {
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = space as _;
+
+        // SAFETY: the control buffer is large enough for the header and the descriptors
+        unsafe {
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_SOCKET;
+            (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of_val(fds) as u32) as _;
+
+            let data = libc::CMSG_DATA(cmsg).cast::<RawFd>();
+            for (i, fd) in fds.iter().enumerate() {
+                ptr::write_unaligned(data.add(i), fd.as_raw_fd());
+            }
+        }
+    }
+
+    // SAFETY: `msg` points to the buffers which are alive during the call
+    let n = unsafe { libc::sendmsg(socket, &msg, SEND_FLAGS) };
+    if n < 0 {
+        return Err(Error::last_os_error());
+    }
+
+    Ok(n as usize)
+}
+
+/// Receive the data and up to `max_fds` file descriptors passed as `SCM_RIGHTS` control message.
+/// Descriptors which don't fit are closed by the kernel.
+pub(crate) fn recv_with_fds(
+    socket: RawFd,
+    buf: &mut [u8],
+    max_fds: usize,
+) -> Result<(usize, Vec<OwnedFd>)> {
+    let mut iov = libc::iovec {
+        iov_base: buf.as_mut_ptr().cast(),
+        iov_len: buf.len(),
+    };
+
+    // SAFETY: all-zero `msghdr` is valid
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+
+    let (mut control, space) = control_buf(max_fds);
+
+    if max_fds != 0 {
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = space as _;
+    }
+
+    // SAFETY: `msg` points to the buffers which are alive during the call
+    let n = unsafe { libc::recvmsg(socket, &mut msg, RECV_FLAGS) };
+    if n < 0 {
+        return Err(Error::last_os_error());
+    }
+
+    let mut fds = Vec::new();
+
+    // SAFETY: the control messages are filled in by the kernel
+    unsafe {
+        let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+
+        while !cmsg.is_null() {
+            if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                let data = libc::CMSG_DATA(cmsg).cast::<RawFd>();
+                let len = (*cmsg).cmsg_len as usize - libc::CMSG_LEN(0) as usize;
+
+                for i in 0..len / mem::size_of::<RawFd>() {
+                    fds.push(OwnedFd::from_raw_fd(ptr::read_unaligned(data.add(i))));
+                }
+            }
+
+            cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+        }
+    }
+
+    Ok((n as usize, fds))
+}
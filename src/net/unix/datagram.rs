@@ -0,0 +1,185 @@
+use super::{ancillary, as_std, SocketAddr};
+use crate::reactor::{io_handle::IoHandle, scheduled_io::Direction};
+use mio::net::UnixDatagram as MioUnixDatagram;
+use std::{
+    fmt,
+    io::Result,
+    net::Shutdown,
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd},
+        unix::net,
+    },
+    path::Path,
+    task::{Context, Poll},
+};
+
+/// Unix domain datagram socket
+pub struct UnixDatagram(IoHandle<MioUnixDatagram>);
+
+impl UnixDatagram {
+    /// Create the socket bound to the path
+    pub fn bind(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(MioUnixDatagram::bind(path)?)
+    }
+
+    /// Create the socket bound to the address, e.g. the one in the abstract namespace
+    pub fn bind_addr(addr: &SocketAddr) -> Result<Self> {
+        Self::from_std(net::UnixDatagram::bind_addr(addr)?)
+    }
+
+    /// Create the socket which is not bound to any address
+    pub fn unbound() -> Result<Self> {
+        Self::new(MioUnixDatagram::unbound()?)
+    }
+
+    /// Create the pair of connected sockets
+    pub fn pair() -> Result<(Self, Self)> {
+        let (a, b) = MioUnixDatagram::pair()?;
+        Ok((Self::new(a)?, Self::new(b)?))
+    }
+
+    /// Create the socket from the standard library one. The socket is switched
+    /// to the non-blocking mode.
+    pub fn from_std(socket: net::UnixDatagram) -> Result<Self> {
+        socket.set_nonblocking(true)?;
+        Self::new(MioUnixDatagram::from_std(socket))
+    }
+
+    /// Deregister the socket from the runtime and turn it into the standard library one.
+    /// The socket stays in the non-blocking mode.
+    pub fn into_std(self) -> Result<net::UnixDatagram> {
+        let socket = self.0.into_inner()?;
+        // SAFETY: the descriptor is owned by the socket which is consumed
+        Ok(unsafe { net::UnixDatagram::from_raw_fd(socket.into_raw_fd()) })
+    }
+
+    fn new(socket: MioUnixDatagram) -> Result<Self> {
+        Ok(Self(IoHandle::new(socket)?))
+    }
+
+    /// Set the default destination for `send` and limit `recv` to the datagrams from this path
+    pub fn connect(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.0.source().connect(path)
+    }
+
+    pub fn connect_addr(&self, addr: &SocketAddr) -> Result<()> {
+        as_std::<net::UnixDatagram>(self).connect_addr(addr)
+    }
+
+    /// Send the datagram to the connected peer
+    pub async fn send(&self, buf: &[u8]) -> Result<usize> {
+        self.0
+            .async_io(Direction::Write, |socket| socket.send(buf))
+            .await
+    }
+
+    pub fn poll_send(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.0
+            .poll_io(cx, Direction::Write, |socket| socket.send(buf))
+    }
+
+    /// Receive the datagram from the connected peer
+    pub async fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        self.0
+            .async_io(Direction::Read, |socket| socket.recv(buf))
+            .await
+    }
+
+    pub fn poll_recv(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.0
+            .poll_io(cx, Direction::Read, |socket| socket.recv(buf))
+    }
+
+    pub async fn send_to(&self, buf: &[u8], path: impl AsRef<Path>) -> Result<usize> {
+        let path = path.as_ref();
+
+        self.0
+            .async_io(Direction::Write, |socket| socket.send_to(buf, path))
+            .await
+    }
+
+    pub async fn send_to_addr(&self, buf: &[u8], addr: &SocketAddr) -> Result<usize> {
+        self.0
+            .async_io(Direction::Write, |socket| {
+                as_std::<net::UnixDatagram>(socket).send_to_addr(buf, addr)
+            })
+            .await
+    }
+
+    pub async fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr)> {
+        self.0
+            .async_io(Direction::Read, |socket| {
+                as_std::<net::UnixDatagram>(socket).recv_from(buf)
+            })
+            .await
+    }
+
+    pub fn poll_recv_from(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<(usize, SocketAddr)>> {
+        self.0.poll_io(cx, Direction::Read, |socket| {
+            as_std::<net::UnixDatagram>(socket).recv_from(buf)
+        })
+    }
+
+    /// Send the datagram to the connected peer along with the file descriptors (`SCM_RIGHTS`)
+    pub async fn send_with_fds(&self, buf: &[u8], fds: &[BorrowedFd<'_>]) -> Result<usize> {
+        self.0
+            .async_io(Direction::Write, |socket| {
+                ancillary::send_with_fds(socket.as_raw_fd(), buf, fds)
+            })
+            .await
+    }
+
+    /// Receive the datagram along with up to `max_fds` file descriptors passed by the peer
+    pub async fn recv_with_fds(
+        &self,
+        buf: &mut [u8],
+        max_fds: usize,
+    ) -> Result<(usize, Vec<OwnedFd>)> {
+        self.0
+            .async_io(Direction::Read, |socket| {
+                ancillary::recv_with_fds(socket.as_raw_fd(), buf, max_fds)
+            })
+            .await
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        as_std::<net::UnixDatagram>(self).local_addr()
+    }
+
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        as_std::<net::UnixDatagram>(self).peer_addr()
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> Result<Option<std::io::Error>> {
+        self.0.source().take_error()
+    }
+
+    /// Shut down the read, write, or both halves of the socket
+    pub fn shutdown(&self, how: Shutdown) -> Result<()> {
+        self.0.source().shutdown(how)
+    }
+}
+
+impl fmt::Debug for UnixDatagram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.source().fmt(f)
+    }
+}
+
+impl AsRawFd for UnixDatagram {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.source().as_raw_fd()
+    }
+}
+
+impl AsFd for UnixDatagram {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the descriptor is owned by the socket and lives as long as it does
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
@@ -0,0 +1,133 @@
+pub mod datagram;
+pub mod pipe;
+pub mod split;
+pub mod stream;
+
+mod ancillary;
+mod ucred;
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests;
+
+pub use self::{datagram::UnixDatagram, stream::UnixStream, ucred::UCred};
+pub use std::os::unix::net::SocketAddr;
+
+use crate::reactor::{io_handle::IoHandle, scheduled_io::Direction};
+use futures::Stream;
+use mio::{net::UnixListener as MioUnixListener, Interest};
+use std::{
+    fmt,
+    future::poll_fn,
+    io::Result,
+    mem::ManuallyDrop,
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, RawFd},
+        unix::net,
+    },
+    path::Path,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// Unix domain socket server
+pub struct UnixListener(IoHandle<MioUnixListener>);
+
+impl UnixListener {
+    /// Create the listener bound to the path
+    pub fn bind(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(MioUnixListener::bind(path)?)
+    }
+
+    /// Create the listener bound to the address, e.g. the one in the abstract namespace
+    /// created with `SocketAddr::from_abstract_name` on Linux
+    pub fn bind_addr(addr: &SocketAddr) -> Result<Self> {
+        Self::from_std(net::UnixListener::bind_addr(addr)?)
+    }
+
+    /// Create the listener from the standard library one. The listener is switched
+    /// to the non-blocking mode.
+    pub fn from_std(listener: net::UnixListener) -> Result<Self> {
+        listener.set_nonblocking(true)?;
+        Self::new(MioUnixListener::from_std(listener))
+    }
+
+    /// Deregister the listener from the runtime and turn it into the standard library one.
+    /// The listener stays in the non-blocking mode.
+    pub fn into_std(self) -> Result<net::UnixListener> {
+        let listener = self.0.into_inner()?;
+        // SAFETY: the descriptor is owned by the listener which is consumed
+        Ok(unsafe { net::UnixListener::from_raw_fd(listener.into_raw_fd()) })
+    }
+
+    fn new(listener: MioUnixListener) -> Result<Self> {
+        Ok(Self(IoHandle::with_interest(listener, Interest::READABLE)?))
+    }
+
+    /// Accept the new incoming connection
+    pub async fn accept(&self) -> Result<(UnixStream, SocketAddr)> {
+        poll_fn(|cx| self.poll_accept(cx)).await
+    }
+
+    pub fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<Result<(UnixStream, SocketAddr)>> {
+        // The standard library accept provides the peer address of the standard type
+        let (stream, addr) = ready!(self.0.poll_io(cx, Direction::Read, |listener| {
+            as_std::<net::UnixListener>(listener).accept()
+        }))?;
+
+        Poll::Ready(Ok((UnixStream::from_std(stream)?, addr)))
+    }
+
+    /// Stream of the incoming connections
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming(self)
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        as_std::<net::UnixListener>(self).local_addr()
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> Result<Option<std::io::Error>> {
+        self.0.source().take_error()
+    }
+}
+
+impl fmt::Debug for UnixListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.source().fmt(f)
+    }
+}
+
+impl AsRawFd for UnixListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.source().as_raw_fd()
+    }
+}
+
+impl AsFd for UnixListener {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the descriptor is owned by the listener and lives as long as it does
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
+
+/// Stream of the connections accepted by the `UnixListener`
+pub struct Incoming<'a>(&'a UnixListener);
+
+impl Stream for Incoming<'_> {
+    type Item = Result<(UnixStream, SocketAddr)>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.poll_accept(cx).map(Some)
+    }
+}
+
+/// Borrow the socket as the standard library one, which provides the addresses of
+/// the standard `SocketAddr` type
+fn as_std<T>(socket: &impl AsRawFd) -> ManuallyDrop<T>
+where
+    T: FromRawFd,
+{
+    // SAFETY: the descriptor is not closed, as the borrowed socket is never dropped
+    ManuallyDrop::new(unsafe { T::from_raw_fd(socket.as_raw_fd()) })
+}
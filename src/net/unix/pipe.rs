@@ -0,0 +1,230 @@
+//! Anonymous pipes and named FIFOs
+
+use crate::reactor::{io_handle::IoHandle, scheduled_io::Direction};
+use futures::{AsyncRead, AsyncWrite};
+use mio::{
+    unix::pipe::{self, Receiver as MioReceiver, Sender as MioSender},
+    Interest,
+};
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{ErrorKind, Read, Result, Write},
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd},
+        unix::fs::{FileTypeExt, OpenOptionsExt},
+    },
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Create the anonymous pipe
+pub fn pipe() -> Result<(Sender, Receiver)> {
+    let (sender, receiver) = pipe::new()?;
+    Ok((Sender::new(sender)?, Receiver::new(receiver)?))
+}
+
+/// Options to open the named FIFO
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    read_write: bool,
+    unchecked: bool,
+}
+
+impl OpenOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Open the FIFO for both reading and writing. It keeps the receiver from getting the end
+    /// of file once all of the writers are gone, and the sender from failing if there are no
+    /// readers yet. Linux only.
+    #[cfg(target_os = "linux")]
+    pub fn read_write(mut self, val: bool) -> Self {
+        self.read_write = val;
+        self
+    }
+
+    /// Skip the check that the file is a FIFO
+    pub fn unchecked(mut self, val: bool) -> Self {
+        self.unchecked = val;
+        self
+    }
+
+    pub fn open_receiver(&self, path: impl AsRef<Path>) -> Result<Receiver> {
+        let file = self.open(path.as_ref(), true)?;
+        // SAFETY: the descriptor is owned by the file which is consumed
+        Receiver::new(unsafe { MioReceiver::from_raw_fd(file.into_raw_fd()) })
+    }
+
+    /// Open the writing end of the FIFO. Fails with `ENXIO` if there are no readers, unless
+    /// opened with `read_write`.
+    pub fn open_sender(&self, path: impl AsRef<Path>) -> Result<Sender> {
+        let file = self.open(path.as_ref(), false)?;
+        // SAFETY: the descriptor is owned by the file which is consumed
+        Sender::new(unsafe { MioSender::from_raw_fd(file.into_raw_fd()) })
+    }
+
+    fn open(&self, path: &Path, read: bool) -> Result<File> {
+        let file = fs::OpenOptions::new()
+            .read(read || self.read_write)
+            .write(!read || self.read_write)
+            .custom_flags(libc::O_NONBLOCK | libc::O_CLOEXEC)
+            .open(path)?;
+
+        if !self.unchecked && !file.metadata()?.file_type().is_fifo() {
+            return Err(std::io::Error::new(ErrorKind::InvalidInput, "not a FIFO"));
+        }
+
+        Ok(file)
+    }
+}
+
+/// Writing end of the pipe
+pub struct Sender(IoHandle<MioSender>);
+
+/// Reading end of the pipe
+pub struct Receiver(IoHandle<MioReceiver>);
+
+impl Sender {
+    fn new(sender: MioSender) -> Result<Self> {
+        Ok(Self(IoHandle::with_interest(sender, Interest::WRITABLE)?))
+    }
+
+    /// Register the writing end of the pipe, e.g. the one of the child process. The descriptor
+    /// is switched to the non-blocking mode.
+    pub fn from_owned_fd(fd: OwnedFd) -> Result<Self> {
+        // SAFETY: the descriptor is owned by the caller and handed over
+        let sender = unsafe { MioSender::from_raw_fd(fd.into_raw_fd()) };
+        sender.set_nonblocking(true)?;
+        Self::new(sender)
+    }
+
+    /// Deregister the pipe and turn it back into the blocking descriptor, e.g. to pass it to
+    /// the child process with `Stdio::from`
+    pub fn into_blocking_fd(self) -> Result<OwnedFd> {
+        let sender = self.0.into_inner()?;
+        sender.set_nonblocking(false)?;
+        // SAFETY: the descriptor is owned by the sender which is consumed
+        Ok(unsafe { OwnedFd::from_raw_fd(sender.into_raw_fd()) })
+    }
+
+    pub(crate) fn io(&self) -> &IoHandle<MioSender> {
+        &self.0
+    }
+
+    /// Wait until the pipe becomes writable
+    pub async fn writable(&self) -> Result<()> {
+        self.0.ready(Direction::Write).await;
+        Ok(())
+    }
+
+    /// Try to write without waiting, `WouldBlock` is returned if the pipe is not ready
+    pub fn try_write(&self, buf: &[u8]) -> Result<usize> {
+        self.0
+            .try_io(Direction::Write, |mut sender| sender.write(buf))
+    }
+}
+
+impl Receiver {
+    fn new(receiver: MioReceiver) -> Result<Self> {
+        Ok(Self(IoHandle::with_interest(receiver, Interest::READABLE)?))
+    }
+
+    /// Register the reading end of the pipe, e.g. the one of the child process. The descriptor
+    /// is switched to the non-blocking mode.
+    pub fn from_owned_fd(fd: OwnedFd) -> Result<Self> {
+        // SAFETY: the descriptor is owned by the caller and handed over
+        let receiver = unsafe { MioReceiver::from_raw_fd(fd.into_raw_fd()) };
+        receiver.set_nonblocking(true)?;
+        Self::new(receiver)
+    }
+
+    /// Deregister the pipe and turn it back into the blocking descriptor, e.g. to pass it to
+    /// the child process with `Stdio::from`
+    pub fn into_blocking_fd(self) -> Result<OwnedFd> {
+        let receiver = self.0.into_inner()?;
+        receiver.set_nonblocking(false)?;
+        // SAFETY: the descriptor is owned by the receiver which is consumed
+        Ok(unsafe { OwnedFd::from_raw_fd(receiver.into_raw_fd()) })
+    }
+
+    pub(crate) fn io(&self) -> &IoHandle<MioReceiver> {
+        &self.0
+    }
+
+    /// Wait until the pipe becomes readable
+    pub async fn readable(&self) -> Result<()> {
+        self.0.ready(Direction::Read).await;
+        Ok(())
+    }
+
+    /// Try to read without waiting, `WouldBlock` is returned if the pipe is not ready
+    pub fn try_read(&self, buf: &mut [u8]) -> Result<usize> {
+        self.0
+            .try_io(Direction::Read, |mut receiver| receiver.read(buf))
+    }
+}
+
+impl AsyncWrite for Sender {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.0.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.0.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl AsyncRead for Receiver {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.0.poll_read(cx, buf)
+    }
+}
+
+impl fmt::Debug for Sender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.source().fmt(f)
+    }
+}
+
+impl fmt::Debug for Receiver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.source().fmt(f)
+    }
+}
+
+impl AsRawFd for Sender {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.source().as_raw_fd()
+    }
+}
+
+impl AsRawFd for Receiver {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.source().as_raw_fd()
+    }
+}
+
+impl AsFd for Sender {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the descriptor is owned by the pipe and lives as long as it does
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
+
+impl AsFd for Receiver {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the descriptor is owned by the pipe and lives as long as it does
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
@@ -0,0 +1,21 @@
+use super::stream::UnixStream;
+use crate::net::split;
+
+pub(super) use crate::net::split::{into_split, split};
+
+/// Borrowed read half of the `UnixStream`
+pub type ReadHalf<'a> = split::ReadHalf<'a, UnixStream>;
+
+/// Borrowed write half of the `UnixStream`
+pub type WriteHalf<'a> = split::WriteHalf<'a, UnixStream>;
+
+/// Owned read half of the `UnixStream`, which may be moved to another task
+pub type OwnedReadHalf = split::OwnedReadHalf<UnixStream>;
+
+/// Owned write half of the `UnixStream`, which may be moved to another task.
+///
+/// The write half of the connection is shut down when it's dropped.
+pub type OwnedWriteHalf = split::OwnedWriteHalf<UnixStream>;
+
+/// Halves passed to `reunite` are not from the same stream
+pub type ReuniteError = split::ReuniteError<UnixStream>;
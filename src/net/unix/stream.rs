@@ -0,0 +1,238 @@
+use super::split::{self, OwnedReadHalf, OwnedWriteHalf, ReadHalf, WriteHalf};
+use super::{ancillary, as_std, ucred, SocketAddr, UCred};
+use crate::reactor::{io_handle::IoHandle, scheduled_io::Direction};
+use futures::{AsyncRead, AsyncWrite};
+use mio::net::UnixStream as MioUnixStream;
+use socket2::{Domain, SockAddr, SockRef, Socket, Type};
+use std::{
+    fmt,
+    io::{self, ErrorKind, Result},
+    net::Shutdown,
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd},
+        unix::net,
+    },
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(target_os = "linux")]
+use std::{
+    ffi::OsStr,
+    os::{linux::net::SocketAddrExt, unix::ffi::OsStrExt},
+};
+
+/// Unix domain stream socket
+pub struct UnixStream(IoHandle<MioUnixStream>);
+
+impl UnixStream {
+    /// Connect to the socket bound to the path
+    pub async fn connect(path: impl AsRef<Path>) -> Result<Self> {
+        Self::connect_addr(&SocketAddr::from_pathname(path)?).await
+    }
+
+    /// Connect to the socket bound to the address, e.g. the one in the abstract namespace.
+    ///
+    /// If the listener's backlog is full, `WouldBlock` is returned like the standard library does.
+    pub async fn connect_addr(addr: &SocketAddr) -> Result<Self> {
+        let addr = sock_addr(addr)?;
+
+        let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+        socket.set_nonblocking(true)?;
+        let stream = Self::new(MioUnixStream::from_std(socket.into()))?;
+
+        // The full listener backlog fails with `WouldBlock`, as the kernel doesn't report when
+        // it has room again
+        match SockRef::from(&stream).connect(&addr) {
+            Ok(()) => return Ok(stream),
+            Err(ref e) if e.raw_os_error() == Some(libc::EINPROGRESS) => {}
+            Err(e) => return Err(e),
+        }
+
+        // The socket becomes writable when the connection is either established or failed
+        stream.0.ready(Direction::Write).await;
+
+        match stream.take_error()? {
+            Some(e) => Err(e),
+            None => Ok(stream),
+        }
+    }
+
+    /// Create the pair of connected streams
+    pub fn pair() -> Result<(Self, Self)> {
+        let (a, b) = MioUnixStream::pair()?;
+        Ok((Self::new(a)?, Self::new(b)?))
+    }
+
+    /// Create the stream from the standard library one. The stream is switched
+    /// to the non-blocking mode.
+    pub fn from_std(stream: net::UnixStream) -> Result<Self> {
+        stream.set_nonblocking(true)?;
+        Self::new(MioUnixStream::from_std(stream))
+    }
+
+    /// Deregister the stream from the runtime and turn it into the standard library one.
+    /// The stream stays in the non-blocking mode.
+    pub fn into_std(self) -> Result<net::UnixStream> {
+        let stream = self.0.into_inner()?;
+        // SAFETY: the descriptor is owned by the stream which is consumed
+        Ok(unsafe { net::UnixStream::from_raw_fd(stream.into_raw_fd()) })
+    }
+
+    pub(crate) fn io(&self) -> &IoHandle<MioUnixStream> {
+        &self.0
+    }
+
+    pub(crate) fn new(stream: MioUnixStream) -> Result<Self> {
+        Ok(Self(IoHandle::new(stream)?))
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        as_std::<net::UnixStream>(self).local_addr()
+    }
+
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        as_std::<net::UnixStream>(self).peer_addr()
+    }
+
+    /// Credentials of the peer process
+    pub fn peer_cred(&self) -> Result<UCred> {
+        ucred::peer_cred(self.as_raw_fd())
+    }
+
+    /// Get the value of the `SO_ERROR` option on this socket
+    pub fn take_error(&self) -> Result<Option<std::io::Error>> {
+        self.0.source().take_error()
+    }
+
+    /// Shut down the read, write, or both halves of the connection
+    pub fn shutdown(&self, how: Shutdown) -> Result<()> {
+        self.0.source().shutdown(how)
+    }
+
+    /// Receive the data without removing it from the queue
+    pub async fn peek(&self, buf: &mut [u8]) -> Result<usize> {
+        self.0
+            .async_io(Direction::Read, |stream| peek(stream, buf))
+            .await
+    }
+
+    pub fn poll_peek(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.0
+            .poll_io(cx, Direction::Read, |stream| peek(stream, buf))
+    }
+
+    /// Split the stream into the read and write halves borrowing it
+    pub fn split(&mut self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        split::split(self)
+    }
+
+    /// Split the stream into the owned halves, which may be moved to different tasks
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        split::into_split(self)
+    }
+
+    /// Send the data along with the file descriptors (`SCM_RIGHTS`). The descriptors are
+    /// delivered with the first byte of the data, so the data must not be empty.
+    pub async fn send_with_fds(&self, buf: &[u8], fds: &[BorrowedFd<'_>]) -> Result<usize> {
+        self.0
+            .async_io(Direction::Write, |stream| {
+                ancillary::send_with_fds(stream.as_raw_fd(), buf, fds)
+            })
+            .await
+    }
+
+    /// Receive the data along with up to `max_fds` file descriptors passed by the peer
+    pub async fn recv_with_fds(
+        &self,
+        buf: &mut [u8],
+        max_fds: usize,
+    ) -> Result<(usize, Vec<OwnedFd>)> {
+        self.0
+            .async_io(Direction::Read, |stream| {
+                ancillary::recv_with_fds(stream.as_raw_fd(), buf, max_fds)
+            })
+            .await
+    }
+}
+
+/// Convert the standard library address into the one for the `connect` call
+fn sock_addr(addr: &SocketAddr) -> Result<SockAddr> {
+    if let Some(path) = addr.as_pathname() {
+        return SockAddr::unix(path);
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(name) = addr.as_abstract_name() {
+        // Abstract names are passed with the leading nul byte
+        return SockAddr::unix(OsStr::from_bytes(&[&[0], name].concat()));
+    }
+
+    Err(io::Error::new(
+        ErrorKind::InvalidInput,
+        "cannot connect to the unnamed address",
+    ))
+}
+
+fn peek(stream: &MioUnixStream, buf: &mut [u8]) -> Result<usize> {
+    // SAFETY: the buffer is valid for writes of its length
+    let n = unsafe {
+        libc::recv(
+            stream.as_raw_fd(),
+            buf.as_mut_ptr().cast(),
+            buf.len(),
+            libc::MSG_PEEK,
+        )
+    };
+
+    if n < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(n as usize)
+}
+
+impl AsyncRead for UnixStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.0.poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for UnixStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.0.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.0.poll_flush(cx)
+    }
+
+    /// Shut down the write half of the connection, the read half stays open
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(self.shutdown(Shutdown::Write))
+    }
+}
+
+impl fmt::Debug for UnixStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.source().fmt(f)
+    }
+}
+
+impl AsRawFd for UnixStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.source().as_raw_fd()
+    }
+}
+
+impl AsFd for UnixStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the descriptor is owned by the stream and lives as long as it does
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::net::unix::{pipe, SocketAddr, UnixDatagram, UnixListener, UnixStream};
+use futures::{executor::block_on, AsyncReadExt, AsyncWriteExt};
+use std::{
+    fs::File,
+    io::{Read, Seek, Write},
+    os::{fd::AsFd, linux::net::SocketAddrExt},
+};
+
+#[test]
+fn test_listener() {
+    crate::test_runtime();
+
+    let path = std::env::temp_dir().join(format!("asynk-{}.sock", std::process::id()));
+    std::fs::remove_file(&path).ok();
+
+    let handle = crate::spawn({
+        let path = path.clone();
+        async move {
+            let listener = UnixListener::bind(&path).unwrap();
+
+            let mut client = UnixStream::connect(&path).await.unwrap();
+            let (mut server, _) = listener.accept().await.unwrap();
+
+            client.write_all(b"ping").await.unwrap();
+            client.close().await.unwrap();
+
+            let mut buf = Vec::new();
+            server.read_to_end(&mut buf).await.unwrap();
+
+            let cred = server.peer_cred().unwrap();
+            let local_path = listener.local_addr().unwrap().as_pathname().map(Into::into);
+
+            (buf, cred.pid(), local_path)
+        }
+    });
+
+    let (buf, pid, local_path) = block_on(handle).unwrap();
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(buf, b"ping");
+    assert_eq!(pid, Some(std::process::id() as i32));
+    assert_eq!(local_path, Some(path));
+}
+
+#[test]
+fn test_abstract_namespace() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let name = format!("asynk-{}", std::process::id());
+        let addr = SocketAddr::from_abstract_name(&name).unwrap();
+
+        let listener = UnixListener::bind_addr(&addr).unwrap();
+        let _client = UnixStream::connect_addr(&addr).await.unwrap();
+        listener.accept().await.unwrap();
+
+        let local_addr = listener.local_addr().unwrap();
+        local_addr.as_abstract_name() == Some(name.as_bytes())
+    });
+
+    assert!(block_on(handle).unwrap());
+}
+
+#[test]
+fn test_full_backlog() {
+    use socket2::{Domain, SockAddr, Socket, Type};
+
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let name = format!("asynk-backlog-{}", std::process::id());
+        let addr = SocketAddr::from_abstract_name(&name).unwrap();
+
+        // The listener never accepts, so its backlog is filled by a few connections
+        let listener = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+        listener
+            .bind(&SockAddr::unix(format!("\0{name}")).unwrap())
+            .unwrap();
+        listener.listen(0).unwrap();
+
+        let mut clients = Vec::new();
+        loop {
+            match UnixStream::connect_addr(&addr).await {
+                Ok(client) => clients.push(client),
+                Err(e) => break (e.kind(), clients.len()),
+            }
+        }
+    });
+
+    let (kind, connected) = block_on(handle).unwrap();
+    assert_eq!(kind, std::io::ErrorKind::WouldBlock);
+    assert!(connected > 0);
+}
+
+#[test]
+fn test_split() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let (a, mut b) = UnixStream::pair().unwrap();
+
+        let echo = crate::spawn(async move {
+            // The inherent method is shadowed by `AsyncReadExt::split` taking the stream by value
+            let (mut read, mut write) = UnixStream::split(&mut b);
+            futures::io::copy(&mut read, &mut write).await.unwrap();
+        });
+
+        let (mut read, mut write) = a.into_split();
+        write.write_all(b"echo").await.unwrap();
+        // Dropping the write half shuts it down, so the echo task sees the end of stream
+        drop(write);
+
+        let mut buf = Vec::new();
+        read.read_to_end(&mut buf).await.unwrap();
+        echo.await.unwrap();
+        buf
+    });
+
+    assert_eq!(block_on(handle).unwrap(), b"echo");
+}
+
+#[test]
+fn test_peek() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let (mut a, mut b) = UnixStream::pair().unwrap();
+        a.write_all(b"data").await.unwrap();
+
+        let mut peeked = [0; 4];
+        let n = b.peek(&mut peeked).await.unwrap();
+
+        let mut buf = [0; 4];
+        b.read_exact(&mut buf).await.unwrap();
+
+        (peeked[..n].to_vec(), buf)
+    });
+
+    let (peeked, buf) = block_on(handle).unwrap();
+    assert!(b"data".starts_with(&peeked) && !peeked.is_empty());
+    assert_eq!(&buf, b"data");
+}
+
+#[test]
+fn test_pass_fds() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let (a, b) = UnixStream::pair().unwrap();
+
+        let mut file = tempfile();
+        file.write_all(b"shared file").unwrap();
+
+        a.send_with_fds(b"fd", &[file.as_fd()]).await.unwrap();
+
+        let mut buf = [0; 2];
+        let (n, mut fds) = b.recv_with_fds(&mut buf, 4).await.unwrap();
+        assert_eq!((&buf[..n], fds.len()), (&b"fd"[..], 1));
+
+        let mut received = File::from(fds.remove(0));
+        received.rewind().unwrap();
+
+        let mut content = String::new();
+        received.read_to_string(&mut content).unwrap();
+        content
+    });
+
+    assert_eq!(block_on(handle).unwrap(), "shared file");
+}
+
+#[test]
+fn test_datagram() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let (a, b) = UnixDatagram::pair().unwrap();
+
+        a.send(b"first").await.unwrap();
+        a.send(b"second").await.unwrap();
+
+        let mut buf = [0; 16];
+        let n = b.recv(&mut buf).await.unwrap();
+        let first = buf[..n].to_vec();
+        let (n, addr) = b.recv_from(&mut buf).await.unwrap();
+
+        (first, buf[..n].to_vec(), addr.is_unnamed())
+    });
+
+    assert_eq!(
+        block_on(handle).unwrap(),
+        (b"first".to_vec(), b"second".to_vec(), true)
+    );
+}
+
+fn tempfile() -> File {
+    let path = std::env::temp_dir().join(format!("asynk-fd-{}", std::process::id()));
+    let file = File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .unwrap();
+    std::fs::remove_file(path).unwrap();
+    file
+}
+
+#[test]
+fn test_pipe() {
+    crate::test_runtime();
+
+    let path = std::env::temp_dir().join(format!("asynk-{}.fifo", std::process::id()));
+    std::fs::remove_file(&path).ok();
+
+    let handle = crate::spawn({
+        let path = path.clone();
+        async move {
+            let (mut sender, mut receiver) = pipe::pipe().unwrap();
+            sender.write_all(b"anonymous").await.unwrap();
+            drop(sender);
+
+            let mut anonymous = Vec::new();
+            receiver.read_to_end(&mut anonymous).await.unwrap();
+
+            let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+            // SAFETY: the path is the valid nul-terminated string
+            assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+            // The reader must be open first, otherwise the sender fails with `ENXIO`
+            let mut receiver = pipe::OpenOptions::new().open_receiver(&path).unwrap();
+            let mut sender = pipe::OpenOptions::new().open_sender(&path).unwrap();
+            sender.write_all(b"named").await.unwrap();
+            drop(sender);
+
+            let mut named = Vec::new();
+            receiver.read_to_end(&mut named).await.unwrap();
+
+            (anonymous, named)
+        }
+    });
+
+    let (anonymous, named) = block_on(handle).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(
+        (&anonymous[..], &named[..]),
+        (&b"anonymous"[..], &b"named"[..])
+    );
+}
@@ -0,0 +1,75 @@
+use std::{
+    io::{Error, Result},
+    os::fd::RawFd,
+};
+
+/// Credentials of the process on the other end of the Unix socket
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UCred {
+    pid: Option<libc::pid_t>,
+    uid: libc::uid_t,
+    gid: libc::gid_t,
+}
+
+impl UCred {
+    /// Process id, if it's provided by the platform
+    pub fn pid(&self) -> Option<libc::pid_t> {
+        self.pid
+    }
+
+    pub fn uid(&self) -> libc::uid_t {
+        self.uid
+    }
+
+    pub fn gid(&self) -> libc::gid_t {
+        self.gid
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn peer_cred(socket: RawFd) -> Result<UCred> {
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+
+    // SAFETY: `cred` and `len` are valid for writes
+    let res = unsafe {
+        libc::getsockopt(
+            socket,
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            (&mut cred as *mut libc::ucred).cast(),
+            &mut len,
+        )
+    };
+
+    if res != 0 {
+        return Err(Error::last_os_error());
+    }
+
+    Ok(UCred {
+        pid: Some(cred.pid),
+        uid: cred.uid,
+        gid: cred.gid,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn peer_cred(socket: RawFd) -> Result<UCred> {
+    let mut uid = 0;
+    let mut gid = 0;
+
+    // SAFETY: `uid` and `gid` are valid for writes
+    if unsafe { libc::getpeereid(socket, &mut uid, &mut gid) } != 0 {
+        return Err(Error::last_os_error());
+    }
+
+    Ok(UCred {
+        pid: None,
+        uid,
+        gid,
+    })
+}
@@ -0,0 +1,352 @@
+//! Child processes.
+//!
+//! The exit of the child is awaited with the pidfd registered in the reactor. On the systems
+//! without pidfd the blocking pool waits for it instead. The children dropped before they were
+//! reaped are reaped in background on `SIGCHLD`.
+
+mod orphan;
+mod stdio;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::stdio::{ChildStderr, ChildStdin, ChildStdout};
+pub use std::process::{ExitStatus, Output, Stdio};
+
+use crate::{io::unix::AsyncFd, task::spawn_blocking};
+use futures::{AsyncRead, AsyncReadExt};
+use std::{
+    ffi::OsStr,
+    fmt, io,
+    os::fd::OwnedFd,
+    path::Path,
+    process::{self, Child as StdChild},
+};
+
+/// Builder of the child process, see `std::process::Command`
+pub struct Command {
+    std: process::Command,
+    kill_on_drop: bool,
+    /// Whether the standard streams are configured by the caller, in order of their descriptors
+    stdio_set: [bool; 3],
+}
+
+impl Command {
+    pub fn new(program: impl AsRef<OsStr>) -> Self {
+        Self {
+            std: process::Command::new(program),
+            kill_on_drop: false,
+            stdio_set: [false; 3],
+        }
+    }
+
+    pub fn arg(&mut self, arg: impl AsRef<OsStr>) -> &mut Self {
+        self.std.arg(arg);
+        self
+    }
+
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.std.args(args);
+        self
+    }
+
+    pub fn env(&mut self, key: impl AsRef<OsStr>, val: impl AsRef<OsStr>) -> &mut Self {
+        self.std.env(key, val);
+        self
+    }
+
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.std.envs(vars);
+        self
+    }
+
+    pub fn env_remove(&mut self, key: impl AsRef<OsStr>) -> &mut Self {
+        self.std.env_remove(key);
+        self
+    }
+
+    pub fn env_clear(&mut self) -> &mut Self {
+        self.std.env_clear();
+        self
+    }
+
+    pub fn current_dir(&mut self, dir: impl AsRef<Path>) -> &mut Self {
+        self.std.current_dir(dir);
+        self
+    }
+
+    pub fn stdin(&mut self, cfg: impl Into<Stdio>) -> &mut Self {
+        self.std.stdin(cfg);
+        self.stdio_set[0] = true;
+        self
+    }
+
+    pub fn stdout(&mut self, cfg: impl Into<Stdio>) -> &mut Self {
+        self.std.stdout(cfg);
+        self.stdio_set[1] = true;
+        self
+    }
+
+    pub fn stderr(&mut self, cfg: impl Into<Stdio>) -> &mut Self {
+        self.std.stderr(cfg);
+        self.stdio_set[2] = true;
+        self
+    }
+
+    /// Kill the child when its `Child` handle is dropped before the exit. Disabled by default.
+    pub fn kill_on_drop(&mut self, kill_on_drop: bool) -> &mut Self {
+        self.kill_on_drop = kill_on_drop;
+        self
+    }
+
+    /// Spawn the child. The piped standard streams are registered in the reactor.
+    pub fn spawn(&mut self) -> io::Result<Child> {
+        Child::new(self.std.spawn()?, self.kill_on_drop)
+    }
+
+    /// Spawn the child and wait for its exit. The standard streams are inherited by default.
+    pub async fn status(&mut self) -> io::Result<ExitStatus> {
+        self.spawn()?.wait().await
+    }
+
+    /// Spawn the child and collect its output. Unless configured otherwise, the standard output
+    /// and error are piped and the standard input is null.
+    pub async fn output(&mut self) -> io::Result<Output> {
+        self.set_default_stdio(Stdio::null, Stdio::piped);
+        let child = self.spawn();
+        // The streams are inherited again, as if `output` has never been called
+        self.set_default_stdio(Stdio::inherit, Stdio::inherit);
+
+        child?.wait_with_output().await
+    }
+
+    /// Configure the standard streams which are not configured by the caller
+    fn set_default_stdio(&mut self, input: fn() -> Stdio, output: fn() -> Stdio) {
+        let [stdin, stdout, stderr] = self.stdio_set;
+
+        if !stdin {
+            self.std.stdin(input());
+        }
+        if !stdout {
+            self.std.stdout(output());
+        }
+        if !stderr {
+            self.std.stderr(output());
+        }
+    }
+}
+
+impl fmt::Debug for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.std.fmt(f)
+    }
+}
+
+/// Spawned child process
+pub struct Child {
+    pub stdin: Option<ChildStdin>,
+    pub stdout: Option<ChildStdout>,
+    pub stderr: Option<ChildStderr>,
+    // `None` only after the child is taken out for reaping on drop
+    std: Option<StdChild>,
+    /// Readable once the child exits. `None` if pidfd is not supported.
+    pidfd: Option<AsyncFd<OwnedFd>>,
+    status: Option<ExitStatus>,
+    kill_on_drop: bool,
+}
+
+impl Child {
+    fn new(mut std: StdChild, kill_on_drop: bool) -> io::Result<Self> {
+        let stdin = std.stdin.take().map(ChildStdin::new).transpose()?;
+        let stdout = std.stdout.take().map(ChildStdout::new).transpose()?;
+        let stderr = std.stderr.take().map(ChildStderr::new).transpose()?;
+        let pidfd = pidfd_open(std.id())?.map(AsyncFd::new).transpose()?;
+
+        Ok(Self {
+            stdin,
+            stdout,
+            stderr,
+            std: Some(std),
+            pidfd,
+            status: None,
+            kill_on_drop,
+        })
+    }
+
+    /// OS identifier of the child, `None` once it has been reaped
+    pub fn id(&self) -> Option<u32> {
+        match self.status {
+            Some(_) => None,
+            None => Some(self.std().id()),
+        }
+    }
+
+    /// Send `SIGKILL` to the child without waiting for its exit
+    pub fn start_kill(&mut self) -> io::Result<()> {
+        match self.status {
+            Some(_) => Ok(()),
+            None => self.std_mut().kill(),
+        }
+    }
+
+    /// Kill the child and wait for its exit
+    pub async fn kill(&mut self) -> io::Result<()> {
+        self.start_kill()?;
+        self.wait().await.map(|_| ())
+    }
+
+    /// Collect the exit status if the child has exited, without waiting
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        if self.status.is_none() {
+            self.status = self.std_mut().try_wait()?;
+        }
+
+        Ok(self.status)
+    }
+
+    /// Wait for the child to exit. The standard input is closed beforehand, so the child
+    /// waiting for its end doesn't deadlock.
+    pub async fn wait(&mut self) -> io::Result<ExitStatus> {
+        drop(self.stdin.take());
+
+        loop {
+            if let Some(status) = self.try_wait()? {
+                return Ok(status);
+            }
+
+            match self.pidfd {
+                Some(ref pidfd) => {
+                    let mut guard = pidfd.readable().await?;
+                    // The child is reaped by `try_wait`, so the readiness is never needed again
+                    // unless it was spurious
+                    guard.clear_ready();
+                }
+                None => {
+                    let pid = self.std().id();
+                    spawn_blocking(move || wait_exit(pid))
+                        .await
+                        .map_err(io::Error::other)??;
+                }
+            }
+        }
+    }
+
+    /// Wait for the child to exit, collecting its standard output and error
+    pub async fn wait_with_output(mut self) -> io::Result<Output> {
+        drop(self.stdin.take());
+        let stdout = self.stdout.take();
+        let stderr = self.stderr.take();
+
+        let (status, stdout, stderr) =
+            futures::try_join!(self.wait(), read_to_end(stdout), read_to_end(stderr))?;
+
+        Ok(Output {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+
+    fn std(&self) -> &StdChild {
+        self.std.as_ref().expect("child is taken")
+    }
+
+    fn std_mut(&mut self) -> &mut StdChild {
+        self.std.as_mut().expect("child is taken")
+    }
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        if self.status.is_some() {
+            return;
+        }
+
+        let Some(mut std) = self.std.take() else {
+            return;
+        };
+
+        if self.kill_on_drop {
+            std.kill().ok();
+        }
+
+        // The child is reaped once it exits, so it doesn't stay a zombie
+        orphan::push(std);
+    }
+}
+
+impl fmt::Debug for Child {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Child")
+            .field("id", &self.id())
+            .field("stdin", &self.stdin)
+            .field("stdout", &self.stdout)
+            .field("stderr", &self.stderr)
+            .finish()
+    }
+}
+
+async fn read_to_end(stream: Option<impl AsyncRead + Unpin>) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    if let Some(mut stream) = stream {
+        stream.read_to_end(&mut buf).await?;
+    }
+    Ok(buf)
+}
+
+/// Descriptor which becomes readable when the process exits. `None` if pidfd is not supported
+/// by the system.
+#[cfg(target_os = "linux")]
+fn pidfd_open(pid: u32) -> io::Result<Option<OwnedFd>> {
+    use std::os::fd::FromRawFd;
+
+    // SAFETY: the syscall has no memory safety preconditions
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+    if fd < 0 {
+        let err = io::Error::last_os_error();
+        return match err.raw_os_error() {
+            Some(libc::ENOSYS) => Ok(None),
+            _ => Err(err),
+        };
+    }
+
+    // SAFETY: the descriptor has just been created and is owned by nobody else
+    Ok(Some(unsafe { OwnedFd::from_raw_fd(fd as i32) }))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pidfd_open(_pid: u32) -> io::Result<Option<OwnedFd>> {
+    Ok(None)
+}
+
+/// Block until the process exits, leaving it for reaping by `try_wait`
+fn wait_exit(pid: u32) -> io::Result<()> {
+    loop {
+        // SAFETY: all-zero is the valid `siginfo_t`
+        let mut info = unsafe { std::mem::zeroed::<libc::siginfo_t>() };
+        // SAFETY: `info` is valid for writing
+        let res = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+
+        match res {
+            0 => return Ok(()),
+            _ if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+            _ => return Err(io::Error::last_os_error()),
+        }
+    }
+}
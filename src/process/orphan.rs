@@ -0,0 +1,60 @@
+use crate::{
+    rt::{AsyncRuntime, Header},
+    signal::unix::{signal, SignalKind},
+};
+use parking_lot::{const_mutex, Mutex};
+use std::{process::Child as StdChild, sync::Once, thread};
+
+/// Children dropped before they were reaped. They're reaped on `SIGCHLD`, so they don't stay
+/// zombies.
+static ORPHANS: Mutex<Vec<StdChild>> = const_mutex(Vec::new());
+
+static REAPER: Once = Once::new();
+
+/// Hand over the child for reaping once it exits. It's called from `Drop`, so it never panics.
+pub(super) fn push(mut child: StdChild) {
+    if !matches!(child.try_wait(), Ok(None)) {
+        return;
+    }
+
+    // Without the runtime, the child is waited for by its own thread
+    let Some(runtime) = crate::try_runtime() else {
+        thread::Builder::new()
+            .name("asynk-orphan".into())
+            .spawn(move || {
+                child.wait().ok();
+            })
+            .ok();
+        return;
+    };
+
+    ORPHANS.lock().push(child);
+    REAPER.call_once(|| spawn_reaper(runtime));
+}
+
+/// Start the task reaping the orphans on every `SIGCHLD`. If the signal can't be handled, the
+/// orphans stay zombies until the process exits.
+fn spawn_reaper(runtime: &AsyncRuntime) {
+    let Ok(mut sigchld) = signal(SignalKind::child()) else {
+        return;
+    };
+
+    let reaper = async move {
+        // The orphans exited before the handler was installed are reaped by the first pass
+        loop {
+            reap();
+
+            if sigchld.recv().await.is_none() {
+                break;
+            }
+        }
+    };
+
+    runtime.spawn_task(reaper, Header::new(None, Default::default()));
+}
+
+fn reap() {
+    ORPHANS
+        .lock()
+        .retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+}
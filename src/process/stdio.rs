@@ -0,0 +1,103 @@
+use crate::reactor::io_handle::IoHandle;
+use futures::{AsyncRead, AsyncWrite};
+use mio::unix::pipe::{Receiver, Sender};
+use std::{
+    fmt,
+    io::Result,
+    os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd},
+    pin::Pin,
+    process,
+    task::{Context, Poll},
+};
+
+/// Writing end of the child's standard input
+pub struct ChildStdin(IoHandle<Sender>);
+
+/// Reading end of the child's standard output
+pub struct ChildStdout(IoHandle<Receiver>);
+
+/// Reading end of the child's standard error
+pub struct ChildStderr(IoHandle<Receiver>);
+
+impl ChildStdin {
+    pub(super) fn new(stdin: process::ChildStdin) -> Result<Self> {
+        let sender = Sender::from(stdin);
+        sender.set_nonblocking(true)?;
+        Ok(Self(IoHandle::new(sender)?))
+    }
+}
+
+impl ChildStdout {
+    pub(super) fn new(stdout: process::ChildStdout) -> Result<Self> {
+        let receiver = Receiver::from(stdout);
+        receiver.set_nonblocking(true)?;
+        Ok(Self(IoHandle::new(receiver)?))
+    }
+}
+
+impl ChildStderr {
+    pub(super) fn new(stderr: process::ChildStderr) -> Result<Self> {
+        let receiver = Receiver::from(stderr);
+        receiver.set_nonblocking(true)?;
+        Ok(Self(IoHandle::new(receiver)?))
+    }
+}
+
+impl AsyncWrite for ChildStdin {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.0.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.0.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl AsyncRead for ChildStdout {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.0.poll_read(cx, buf)
+    }
+}
+
+impl AsyncRead for ChildStderr {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.0.poll_read(cx, buf)
+    }
+}
+
+macro_rules! impl_fd {
+    ($($ty:ty),*) => {$(
+        impl fmt::Debug for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.source().fmt(f)
+            }
+        }
+
+        impl AsRawFd for $ty {
+            fn as_raw_fd(&self) -> RawFd {
+                self.0.source().as_raw_fd()
+            }
+        }
+
+        impl AsFd for $ty {
+            fn as_fd(&self) -> BorrowedFd<'_> {
+                // SAFETY: the descriptor is owned by the pipe and lives as long as it does
+                unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+            }
+        }
+    )*};
+}
+
+impl_fd!(ChildStdin, ChildStdout, ChildStderr);
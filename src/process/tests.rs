@@ -0,0 +1,94 @@
+use crate::process::{Command, Stdio};
+use futures::{executor::block_on, AsyncWriteExt};
+use futures_timer::Delay;
+use std::{os::unix::process::ExitStatusExt, path::Path, time::Duration};
+
+#[test]
+fn test_output() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let mut child = Command::new("sh")
+            .args(["-c", "cat; echo err >&2; exit 3"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(b"hello").await.unwrap();
+        drop(stdin);
+
+        child.wait_with_output().await.unwrap()
+    });
+
+    let output = block_on(handle).unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(
+        (&output.stdout[..], &output.stderr[..]),
+        (&b"hello"[..], &b"err\n"[..])
+    );
+}
+
+#[test]
+fn test_kill() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+        assert!(child.try_wait().unwrap().is_none());
+
+        child.kill().await.unwrap();
+        (child.wait().await.unwrap(), child.id())
+    });
+
+    let (status, id) = block_on(handle).unwrap();
+    assert_eq!(status.signal(), Some(libc::SIGKILL));
+    assert_eq!(id, None);
+}
+
+#[test]
+fn test_output_keeps_config() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "read line; echo \"$line\""]);
+
+        // The input is null, so `read` gets the end of file at once
+        let output = cmd.output().await.unwrap();
+
+        // The piped streams of `output` don't leak into the next spawn
+        let mut child = cmd.stdin(Stdio::null()).spawn().unwrap();
+        let piped = child.stdout.is_some() || child.stderr.is_some();
+        child.wait().await.unwrap();
+
+        (output.stdout, piped)
+    });
+
+    assert_eq!(block_on(handle).unwrap(), (b"\n".to_vec(), false));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_orphan_reaped() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let child = Command::new("sleep").arg("0.1").spawn().unwrap();
+        let proc = format!("/proc/{}", child.id().unwrap());
+        drop(child);
+
+        // The zombie entry disappears once the child is reaped
+        for _ in 0..200 {
+            if !Path::new(&proc).exists() {
+                return true;
+            }
+            Delay::new(Duration::from_millis(10)).await;
+        }
+        false
+    });
+
+    assert!(block_on(handle).unwrap());
+}
@@ -0,0 +1,61 @@
+use super::Reactor;
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// Thread dispatching the reactor events, so the I/O makes progress regardless of what the
+/// runtime's caller thread is doing
+pub struct Driver {
+    reactor: Reactor,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Driver {
+    /// Spawn the thread. If polling fails, the thread stops and the error is passed to
+    /// `on_error`.
+    pub fn spawn(reactor: Reactor, on_error: impl FnOnce(io::Error) + Send + 'static) -> Self {
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread = thread::Builder::new()
+            .name("asynk-driver".into())
+            .spawn({
+                let reactor = reactor.clone();
+                let shutdown = Arc::clone(&shutdown);
+                move || {
+                    while !shutdown.load(Ordering::Acquire) {
+                        // Interrupted waits are retried by `poll_events` itself, any other error
+                        // is persistent
+                        if let Err(e) = reactor.poll_events(None) {
+                            on_error(e);
+                            return;
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn the driver thread");
+
+        Self {
+            reactor,
+            shutdown,
+            thread: Some(thread),
+        }
+    }
+
+    /// Stop the thread and wait for it
+    pub fn shutdown(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+
+        self.shutdown.store(true, Ordering::Release);
+        self.reactor.wake().expect("reactor wake error");
+
+        thread.join().ok();
+    }
+}
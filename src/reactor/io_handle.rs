@@ -1,153 +1,162 @@
-use crate::reactor_global;
-use mio::{event::Source, Interest, Token};
+use super::scheduled_io::{Direction, ReadyEvent, ScheduledIo};
+use crate::{reactor_global, task::coop};
+use mio::{event::Source, Interest};
 use std::{
-    io::{ErrorKind, Read, Result, Write},
-    pin::Pin,
-    task::{Context, Poll, Waker},
+    future::poll_fn,
+    io::{ErrorKind, IoSlice, IoSliceMut, Read, Result, Write},
+    sync::Arc,
+    task::{ready, Context, Poll},
 };
 
+/// Source registered in the reactor.
+///
+/// The source is registered once on creation and its readiness is tracked separately for
+/// reading and writing, so the operations of both directions may be awaited concurrently.
 pub struct IoHandle<S>
 where
     S: Source,
 {
-    source: S,
-    waiting_read: bool,
-    waiting_write: bool,
-    token: Option<Token>,
+    // `None` only after the source is taken out by `into_inner`
+    source: Option<S>,
+    io: Arc<ScheduledIo>,
 }
 
-impl<S> Unpin for IoHandle<S> where S: Source {}
-
 impl<S> IoHandle<S>
 where
     S: Source,
 {
-    pub fn new(source: S) -> Self {
-        Self {
-            source,
-            waiting_read: false,
-            waiting_write: false,
-            token: None,
-        }
+    /// Register the source for both reading and writing
+    pub fn new(source: S) -> Result<Self> {
+        Self::with_interest(source, Interest::READABLE | Interest::WRITABLE)
+    }
+
+    pub fn with_interest(mut source: S, interest: Interest) -> Result<Self> {
+        let io = reactor_global().register(&mut source, interest)?;
+
+        Ok(Self {
+            source: Some(source),
+            io,
+        })
     }
 
     pub fn source(&self) -> &S {
-        &self.source
+        self.source.as_ref().expect("I/O source is taken")
     }
 
-    pub fn register(&mut self, interest: Interest, waker: Waker) -> Result<()> {
-        match self.token {
-            Some(token) => {
-                reactor_global().reregister(token, &mut self.source, interest, waker)?;
-            }
-            None => {
-                let token = reactor_global().register(&mut self.source, interest, waker)?;
-                self.token = Some(token);
-            }
-        };
+    /// Deregister the source from the reactor and take it out
+    pub fn into_inner(mut self) -> Result<S> {
+        let mut source = self.source.take().expect("I/O source is taken");
+        reactor_global().deregister(&self.io, &mut source)?;
+        Ok(source)
+    }
+
+    /// Wait until the source becomes ready for the operations of the given direction
+    pub fn poll_ready(&self, cx: &mut Context<'_>, direction: Direction) -> Poll<ReadyEvent> {
+        self.io.poll_ready(cx, direction)
+    }
+
+    pub async fn ready(&self, direction: Direction) -> ReadyEvent {
+        poll_fn(|cx| self.poll_ready(cx, direction)).await
+    }
+
+    /// Readiness which is known for the direction at the moment
+    pub fn ready_event(&self, direction: Direction) -> ReadyEvent {
+        self.io.ready_event(direction)
+    }
 
-        Ok(())
+    /// Forget the observed readiness, so the next `poll_ready` waits for the new event
+    pub fn clear_readiness(&self, event: ReadyEvent) {
+        self.io.clear_readiness(event)
     }
 
-    pub fn deregister(&mut self) -> Result<()> {
-        match self.token {
-            Some(token) => {
-                reactor_global().deregister(token, &mut self.source)?;
-                self.token = None;
-                Ok(())
+    /// Perform the I/O operation once the source is ready. If the operation would block,
+    /// the readiness is cleared and the task waits for the next event.
+    pub fn poll_io<R>(
+        &self,
+        cx: &mut Context<'_>,
+        direction: Direction,
+        mut f: impl FnMut(&S) -> Result<R>,
+    ) -> Poll<Result<R>> {
+        let coop = ready!(coop::poll_proceed(cx));
+
+        loop {
+            let event = ready!(self.poll_ready(cx, direction));
+
+            match f(self.source()) {
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => self.clear_readiness(event),
+                res => {
+                    coop.made_progress();
+                    return Poll::Ready(res);
+                }
             }
-            None => Ok(()),
         }
     }
+
+    /// Try to perform the I/O operation without waiting. `WouldBlock` is returned if the source
+    /// is not known to be ready.
+    pub fn try_io<R>(&self, direction: Direction, f: impl FnOnce(&S) -> Result<R>) -> Result<R> {
+        let event = self.ready_event(direction);
+
+        if event.ready.is_empty() {
+            return Err(ErrorKind::WouldBlock.into());
+        }
+
+        let res = f(self.source());
+        if matches!(res, Err(ref e) if e.kind() == ErrorKind::WouldBlock) {
+            self.clear_readiness(event);
+        }
+
+        res
+    }
+
+    pub async fn async_io<R>(
+        &self,
+        direction: Direction,
+        mut f: impl FnMut(&S) -> Result<R>,
+    ) -> Result<R> {
+        poll_fn(|cx| self.poll_io(cx, direction, &mut f)).await
+    }
 }
 
 impl<S> IoHandle<S>
 where
-    S: Source + Read,
+    S: Source,
+    for<'a> &'a S: Read,
 {
-    pub fn poll_read(
-        mut self: Pin<&mut Self>,
+    pub fn poll_read(&self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.poll_io(cx, Direction::Read, |mut source| source.read(buf))
+    }
+
+    pub fn poll_read_vectored(
+        &self,
         cx: &mut Context<'_>,
-        buf: &mut [u8],
+        bufs: &mut [IoSliceMut<'_>],
     ) -> Poll<Result<usize>> {
-        if !self.waiting_read {
-            self.register(Interest::READABLE, cx.waker().clone())?;
-            self.waiting_read = true;
-            return Poll::Pending;
-        }
-
-        match self.source.read(buf) {
-            Ok(n) => {
-                if n == 0 {
-                    self.deregister()?;
-                    self.waiting_read = false;
-                }
-
-                Poll::Ready(Ok(n))
-            }
-            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
-                self.deregister()?;
-                self.waiting_read = false;
-                Poll::Ready(Ok(0))
-            }
-            Err(e) => Poll::Ready(Err(e)),
-        }
+        self.poll_io(cx, Direction::Read, |mut source| source.read_vectored(bufs))
     }
 }
 
 impl<S> IoHandle<S>
 where
-    S: Source + Write,
+    S: Source,
+    for<'a> &'a S: Write,
 {
-    pub fn poll_write(
-        mut self: Pin<&mut Self>,
+    pub fn poll_write(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.poll_io(cx, Direction::Write, |mut source| source.write(buf))
+    }
+
+    pub fn poll_write_vectored(
+        &self,
         cx: &mut Context<'_>,
-        buf: &[u8],
+        bufs: &[IoSlice<'_>],
     ) -> Poll<Result<usize>> {
-        if !self.waiting_write {
-            self.register(Interest::WRITABLE, cx.waker().clone())?;
-            self.waiting_write = true;
-            return Poll::Pending;
-        }
-
-        match self.source.write(buf) {
-            Ok(n) => {
-                if n == 0 {
-                    self.deregister()?;
-                    self.waiting_write = false;
-                }
-
-                Poll::Ready(Ok(n))
-            }
-            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
-                self.deregister()?;
-                self.waiting_write = false;
-                Poll::Ready(Ok(0))
-            }
-            Err(e) => Poll::Ready(Err(e)),
-        }
+        self.poll_io(cx, Direction::Write, |mut source| {
+            source.write_vectored(bufs)
+        })
     }
 
-    pub fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        if !self.waiting_write {
-            self.register(Interest::WRITABLE, cx.waker().clone())?;
-            self.waiting_write = true;
-            return Poll::Pending;
-        }
-
-        match self.source.flush() {
-            Ok(()) => {
-                self.deregister()?;
-                self.waiting_write = false;
-                Poll::Ready(Ok(()))
-            }
-            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
-                self.deregister()?;
-                self.waiting_write = false;
-                Poll::Ready(Ok(()))
-            }
-            Err(e) => Poll::Ready(Err(e)),
-        }
+    pub fn poll_flush(&self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_io(cx, Direction::Write, |mut source| source.flush())
     }
 }
 
@@ -156,6 +165,8 @@ where
     S: Source,
 {
     fn drop(&mut self) {
-        self.deregister().unwrap()
+        if let Some(mut source) = self.source.take() {
+            reactor_global().deregister(&self.io, &mut source).ok();
+        }
     }
 }
@@ -1,24 +1,30 @@
+pub mod driver;
 pub mod io_handle;
+pub mod scheduled_io;
 
-use mio::{event::Source, Events, Interest, Poll, Token};
-use parking_lot::RwLock;
+use self::scheduled_io::ScheduledIo;
+use mio::{event::Source, Events, Interest, Poll, Registry, Token, Waker};
+use parking_lot::Mutex;
 use sharded_slab::Slab;
 use std::{
     io::{self, Error},
     sync::Arc,
-    task::Waker,
     time::Duration,
 };
 
-const POLL_EVENTS_TIMEOUT: Duration = Duration::from_millis(1);
+/// Token of the waker interrupting `poll_events`. It's never produced by the slab, as the key
+/// would exceed its thread id and index limits.
+const WAKER_TOKEN: Token = Token(usize::MAX);
 
 #[derive(Clone)]
 pub struct Reactor(Arc<Inner>);
 
 struct Inner {
-    wakers: Slab<Waker>,
-    poll: RwLock<Poll>,
-    events: RwLock<Events>,
+    registrations: Slab<Arc<ScheduledIo>>,
+    registry: Registry,
+    poll: Mutex<Poll>,
+    events: Mutex<Events>,
+    waker: Waker,
 }
 
 impl Default for Reactor {
@@ -29,90 +35,81 @@ impl Default for Reactor {
 
 impl Reactor {
     pub fn new() -> Self {
+        let poll = Poll::new().unwrap();
+        let registry = poll.registry().try_clone().unwrap();
+        let waker = Waker::new(&registry, WAKER_TOKEN).unwrap();
+
         Self(Arc::new(Inner {
-            poll: RwLock::new(Poll::new().unwrap()),
-            events: RwLock::new(Events::with_capacity(128)),
-            wakers: Slab::new(),
+            registrations: Slab::new(),
+            registry,
+            poll: Mutex::new(poll),
+            events: Mutex::new(Events::with_capacity(128)),
+            waker,
         }))
     }
 
-    /// Register interested events for the given source
-    pub fn register<S>(
-        &self,
-        source: &mut S,
-        interests: Interest,
-        waker: Waker,
-    ) -> io::Result<Token>
+    /// Register interested events for the given source. The readiness of the source is tracked
+    /// by the returned `ScheduledIo` until the source is deregistered.
+    pub fn register<S>(&self, source: &mut S, interests: Interest) -> io::Result<Arc<ScheduledIo>>
     where
-        S: Source,
+        S: Source + ?Sized,
     {
-        let token = self
+        let entry = self
             .0
-            .wakers
-            .insert(waker)
+            .registrations
+            .vacant_entry()
             .ok_or(Error::other("slab queue is full"))?;
 
-        let token = Token(token);
+        let io = Arc::new(ScheduledIo::new(Token(entry.key())));
 
-        self.0
-            .poll
-            .read()
-            .registry()
-            .register(source, token, interests)?;
+        // The entry must be in place before the registration, as the event may be delivered
+        // immediately
+        entry.insert(Arc::clone(&io));
 
-        Ok(token)
-    }
+        if let Err(e) = self.0.registry.register(source, io.token(), interests) {
+            self.0.registrations.remove(io.token().into());
+            return Err(e);
+        }
 
-    pub fn reregister<S>(
-        &self,
-        token: Token,
-        source: &mut S,
-        interests: Interest,
-        waker: Waker,
-    ) -> io::Result<Token>
-    where
-        S: Source,
-    {
-        self.0.wakers.remove(token.into());
-
-        let new_token = Token(
-            self.0
-                .wakers
-                .insert(waker)
-                .ok_or(Error::other("slab queue is full"))?,
-        );
-
-        self.0
-            .poll
-            .read()
-            .registry()
-            .reregister(source, new_token, interests)?;
-
-        Ok(new_token)
+        Ok(io)
     }
 
-    pub fn poll_events(&self) -> io::Result<()> {
-        let mut poll = self.0.poll.write();
-        let mut events = self.0.events.write();
+    /// Wait for the events and dispatch them to the registered sources. `None` timeout waits
+    /// until any event arrives or `wake` is called.
+    pub fn poll_events(&self, timeout: Option<Duration>) -> io::Result<()> {
+        let mut poll = self.0.poll.lock();
+        let mut events = self.0.events.lock();
 
-        poll.poll(&mut events, Some(POLL_EVENTS_TIMEOUT))?;
+        match poll.poll(&mut events, timeout) {
+            // Signal interrupted the wait, the caller polls again
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => return Ok(()),
+            res => res?,
+        }
 
-        for event in events.into_iter() {
-            if let Some(waker) = self.0.wakers.get(event.token().into()) {
-                waker.wake_by_ref();
+        for event in events.iter() {
+            if event.token() == WAKER_TOKEN {
+                continue;
+            }
+
+            if let Some(io) = self.0.registrations.get(event.token().into()) {
+                io.dispatch(event);
             }
         }
 
         Ok(())
     }
 
+    /// Interrupt the blocked `poll_events`
+    pub fn wake(&self) -> io::Result<()> {
+        self.0.waker.wake()
+    }
+
     /// Remove the interests for the given source
-    pub fn deregister<S>(&self, token: Token, source: &mut S) -> io::Result<()>
+    pub fn deregister<S>(&self, io: &ScheduledIo, source: &mut S) -> io::Result<()>
     where
-        S: Source,
+        S: Source + ?Sized,
     {
-        self.0.poll.read().registry().deregister(source)?;
-        self.0.wakers.get(token.0);
-        Ok(())
+        self.0.registrations.remove(io.token().into());
+        self.0.registry.deregister(source)
     }
 }
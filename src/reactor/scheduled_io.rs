@@ -0,0 +1,177 @@
+use bitflags::bitflags;
+use mio::{event::Event, Token};
+use parking_lot::Mutex;
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+bitflags! {
+    /// Readiness of the registered source
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Ready: usize {
+        const READABLE = 1 << 0;
+        const WRITABLE = 1 << 1;
+        const READ_CLOSED = 1 << 2;
+        const WRITE_CLOSED = 1 << 3;
+        const ERROR = 1 << 4;
+    }
+}
+
+impl Ready {
+    fn from_event(event: &Event) -> Self {
+        let mut ready = Ready::empty();
+
+        if event.is_readable() {
+            ready |= Ready::READABLE;
+        }
+        if event.is_writable() {
+            ready |= Ready::WRITABLE;
+        }
+        if event.is_read_closed() {
+            ready |= Ready::READ_CLOSED;
+        }
+        if event.is_write_closed() {
+            ready |= Ready::WRITE_CLOSED;
+        }
+        if event.is_error() {
+            ready |= Ready::ERROR;
+        }
+
+        ready
+    }
+}
+
+/// Direction of the I/O operation. Every direction has its own readiness and waiting tasks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Read,
+    Write,
+}
+
+impl Direction {
+    fn mask(self) -> Ready {
+        match self {
+            Direction::Read => Ready::READABLE | Ready::READ_CLOSED | Ready::ERROR,
+            Direction::Write => Ready::WRITABLE | Ready::WRITE_CLOSED | Ready::ERROR,
+        }
+    }
+}
+
+/// Readiness observed by the task. The tick tells apart the events delivered by the reactor.
+#[derive(Clone, Copy, Debug)]
+pub struct ReadyEvent {
+    pub ready: Ready,
+    tick: usize,
+}
+
+/// Lower bits of the readiness word store `Ready`, the rest is the tick of the last event
+const TICK_SHIFT: u32 = 16;
+const READY_MASK: usize = (1 << TICK_SHIFT) - 1;
+
+/// Readiness state and waiting tasks of the source registered in the reactor
+pub struct ScheduledIo {
+    token: Token,
+    readiness: AtomicUsize,
+    waiters: Mutex<Waiters>,
+}
+
+#[derive(Default)]
+struct Waiters {
+    reader: Vec<Waker>,
+    writer: Vec<Waker>,
+}
+
+impl Waiters {
+    fn get_mut(&mut self, direction: Direction) -> &mut Vec<Waker> {
+        match direction {
+            Direction::Read => &mut self.reader,
+            Direction::Write => &mut self.writer,
+        }
+    }
+}
+
+impl ScheduledIo {
+    pub fn new(token: Token) -> Self {
+        Self {
+            token,
+            readiness: AtomicUsize::new(0),
+            waiters: Mutex::new(Waiters::default()),
+        }
+    }
+
+    pub fn token(&self) -> Token {
+        self.token
+    }
+
+    /// Return the readiness for the direction, or remember the task waker if the source
+    /// is not ready yet
+    pub fn poll_ready(&self, cx: &mut Context<'_>, direction: Direction) -> Poll<ReadyEvent> {
+        // Readiness is checked under the lock to not miss the event dispatched concurrently
+        let mut waiters = self.waiters.lock();
+
+        let event = self.ready_event(direction);
+        if !event.ready.is_empty() {
+            return Poll::Ready(event);
+        }
+
+        let wakers = waiters.get_mut(direction);
+        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+
+        Poll::Pending
+    }
+
+    /// Current readiness for the direction, without the waker registration
+    pub fn ready_event(&self, direction: Direction) -> ReadyEvent {
+        let readiness = self.readiness.load(Ordering::Acquire);
+
+        ReadyEvent {
+            ready: Ready::from_bits_truncate(readiness & READY_MASK) & direction.mask(),
+            tick: readiness >> TICK_SHIFT,
+        }
+    }
+
+    /// Clear the readiness observed by the task, e.g. after the operation returned `WouldBlock`.
+    ///
+    /// Nothing happens if the reactor delivered a newer event since the readiness was observed,
+    /// otherwise the edge-triggered notification would be lost. Closed states are final and
+    /// never cleared.
+    pub fn clear_readiness(&self, event: ReadyEvent) {
+        let clear = event.ready - Ready::READ_CLOSED - Ready::WRITE_CLOSED;
+
+        self.readiness
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |readiness| {
+                (readiness >> TICK_SHIFT == event.tick).then_some(readiness & !clear.bits())
+            })
+            .ok();
+    }
+
+    /// Apply the event delivered by the reactor and wake up the interested tasks
+    pub fn dispatch(&self, event: &Event) {
+        let ready = Ready::from_event(event);
+
+        let mut waiters = self.waiters.lock();
+
+        self.readiness
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |readiness| {
+                let tick = (readiness >> TICK_SHIFT).wrapping_add(1);
+                let ready = Ready::from_bits_truncate(readiness & READY_MASK) | ready;
+                Some((tick << TICK_SHIFT) | ready.bits())
+            })
+            .ok();
+
+        let mut wakers = Vec::new();
+
+        for direction in [Direction::Read, Direction::Write] {
+            if ready.intersects(direction.mask()) {
+                wakers.append(waiters.get_mut(direction));
+            }
+        }
+
+        drop(waiters);
+
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
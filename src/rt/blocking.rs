@@ -0,0 +1,35 @@
+use super::{
+    handle::JoinError,
+    task::{Cancel, Header},
+};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Closure running on the blocking pool. It can't be interrupted once started, so the
+/// cancellation only has an effect while the closure waits in the queue.
+#[derive(Default)]
+pub(super) struct BlockingTask {
+    cancelled: AtomicBool,
+}
+
+impl BlockingTask {
+    pub fn run<T>(&self, header: &Header, f: impl FnOnce() -> T) -> Result<T, JoinError> {
+        if self.cancelled.load(Ordering::Acquire) {
+            return Err(JoinError::cancelled(header));
+        }
+
+        panic::catch_unwind(AssertUnwindSafe(f))
+            .map_err(|payload| JoinError::panic(header, payload))
+    }
+}
+
+impl Cancel for BlockingTask {
+    fn cancel(self: Arc<Self>) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+}
@@ -1,8 +1,13 @@
 use crate::rt::AsyncRuntime;
 
+const DEFAULT_BLOCKING_THREADS: usize = 8;
+
 #[derive(Default)]
 pub struct AsyncRuntimeBuilder {
     thread_count: Option<usize>,
+    blocking_thread_count: Option<usize>,
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    io_uring: bool,
 }
 
 impl AsyncRuntimeBuilder {
@@ -21,9 +26,33 @@ impl AsyncRuntimeBuilder {
         self
     }
 
+    /// Number of threads running the blocking operations (`task::spawn_blocking`, DNS lookups)
+    pub fn blocking_threads(mut self, val: usize) -> Self {
+        assert!(val != 0, "blocking thread count must be greater than 0");
+
+        self.blocking_thread_count = Some(val);
+        self
+    }
+
+    /// Perform the operations of the `uring` module with io_uring. If the kernel doesn't support
+    /// it, the runtime falls back to epoll.
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    pub fn io_uring(mut self, val: bool) -> Self {
+        self.io_uring = val;
+        self
+    }
+
     pub fn build(self) -> AsyncRuntime {
         let thread_count = self.thread_count.unwrap_or_else(Self::default_thread_count);
-        AsyncRuntime::new(thread_count)
+        let blocking_thread_count = self
+            .blocking_thread_count
+            .unwrap_or(DEFAULT_BLOCKING_THREADS);
+        AsyncRuntime::new(
+            thread_count,
+            blocking_thread_count,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            self.io_uring,
+        )
     }
 
     fn default_thread_count() -> usize {
@@ -1,22 +1,188 @@
+use super::task::{Cancel, Header};
+use crate::task::{coop, Id};
 use futures::{channel::oneshot, FutureExt};
+use parking_lot::Mutex;
 use std::{
+    any::Any,
+    fmt,
     future::Future,
     pin::Pin,
-    task::{Context, Poll},
+    sync::{Arc, Weak},
+    task::{ready, Context, Poll},
 };
 
-#[derive(Debug, thiserror::Error)]
-#[error("join fail: result channel dropped")]
-pub struct JoinError;
+/// Task failed to complete
+pub struct JoinError {
+    id: Id,
+    name: Option<Arc<str>>,
+    repr: Repr,
+}
+
+enum Repr {
+    /// Result channel dropped without sending the value
+    Dropped,
+    /// Task was aborted
+    Cancelled,
+    /// Task panicked. The payload is wrapped into mutex to keep the error `Sync`.
+    Panic(Mutex<Box<dyn Any + Send + 'static>>),
+}
+
+impl JoinError {
+    pub(crate) fn panic(header: &Header, payload: Box<dyn Any + Send + 'static>) -> Self {
+        Self {
+            id: header.id(),
+            name: header.name().cloned(),
+            repr: Repr::Panic(Mutex::new(payload)),
+        }
+    }
+
+    pub(crate) fn cancelled(header: &Header) -> Self {
+        Self {
+            id: header.id(),
+            name: header.name().cloned(),
+            repr: Repr::Cancelled,
+        }
+    }
+
+    fn dropped(id: Id, name: Option<Arc<str>>) -> Self {
+        Self {
+            id,
+            name,
+            repr: Repr::Dropped,
+        }
+    }
+
+    /// Identifier of the failed task
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Name of the failed task, if it was assigned with `task::Builder`
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.repr, Repr::Cancelled)
+    }
+
+    pub fn is_panic(&self) -> bool {
+        matches!(self.repr, Repr::Panic(_))
+    }
+
+    /// Consume the error, returning the panic payload.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the error doesn't represent a panic.
+    pub fn into_panic(self) -> Box<dyn Any + Send + 'static> {
+        self.try_into_panic()
+            .expect("`JoinError` reason is not a panic")
+    }
+
+    pub fn try_into_panic(self) -> Result<Box<dyn Any + Send + 'static>, JoinError> {
+        match self.repr {
+            Repr::Panic(payload) => Ok(payload.into_inner()),
+            _ => Err(self),
+        }
+    }
+
+    fn fmt_task(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(ref name) => write!(f, "task {} ({})", self.id, name),
+            None => write!(f, "task {}", self.id),
+        }
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_task(f)?;
+
+        match self.repr {
+            Repr::Dropped => write!(f, " join fail: result channel dropped"),
+            Repr::Cancelled => write!(f, " was cancelled"),
+            Repr::Panic(ref payload) => {
+                let payload = payload.lock();
+                match payload
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                {
+                    Some(msg) => write!(f, " panicked with message {:?}", msg),
+                    None => write!(f, " panicked"),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.repr {
+            Repr::Dropped => "Dropped",
+            Repr::Cancelled => "Cancelled",
+            Repr::Panic(_) => "Panic",
+        };
 
-pub struct JoinHandle<T>(oneshot::Receiver<T>);
+        f.debug_struct("JoinError")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("reason", &reason)
+            .finish()
+    }
+}
+
+impl std::error::Error for JoinError {}
+
+pub struct JoinHandle<T> {
+    rx: oneshot::Receiver<Result<T, JoinError>>,
+    abort: AbortHandle,
+    name: Option<Arc<str>>,
+}
 
 impl<T> JoinHandle<T>
 where
     T: Send + 'static,
 {
-    pub(crate) fn new(rx: oneshot::Receiver<T>) -> Self {
-        Self(rx)
+    pub(crate) fn new(
+        rx: oneshot::Receiver<Result<T, JoinError>>,
+        task: &Arc<impl Cancel + 'static>,
+        header: &Header,
+    ) -> Self {
+        let task = Arc::downgrade(task);
+
+        Self {
+            rx,
+            abort: AbortHandle {
+                task,
+                id: header.id(),
+            },
+            name: header.name().cloned(),
+        }
+    }
+}
+
+impl<T> JoinHandle<T> {
+    /// Identifier of the task
+    pub fn id(&self) -> Id {
+        self.abort.id
+    }
+
+    /// Name of the task, if it was assigned with `task::Builder`
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Cancel the task. Awaiting the handle of the cancelled task returns the `JoinError`,
+    /// unless the task has already completed.
+    pub fn abort(&self) {
+        self.abort.abort()
+    }
+
+    /// Handle which can cancel the task without awaiting its result
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.abort.clone()
     }
 }
 
@@ -24,6 +190,50 @@ impl<T> Future for JoinHandle<T> {
     type Output = Result<T, JoinError>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.0.poll_unpin(cx).map_err(|_| JoinError)
+        let coop = ready!(coop::poll_proceed(cx));
+        let res = ready!(self.rx.poll_unpin(cx));
+        coop.made_progress();
+
+        match res {
+            Ok(res) => Poll::Ready(res),
+            Err(_) => Poll::Ready(Err(JoinError::dropped(self.id(), self.name.clone()))),
+        }
+    }
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("id", &self.id())
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// Owned permission to cancel the task. The task isn't kept alive by the handle: once its
+/// future drops every waker, the task is dropped and awaiting it fails with the `JoinError`.
+#[derive(Clone)]
+pub struct AbortHandle {
+    task: Weak<dyn Cancel>,
+    id: Id,
+}
+
+impl AbortHandle {
+    /// Identifier of the task
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Cancel the task. Does nothing if the task has already completed.
+    pub fn abort(&self) {
+        if let Some(task) = self.task.upgrade() {
+            task.cancel()
+        }
+    }
+}
+
+impl fmt::Debug for AbortHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AbortHandle").field("id", &self.id).finish()
     }
 }
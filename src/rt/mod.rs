@@ -1,18 +1,30 @@
 pub mod builder;
 pub mod handle;
 
-mod task;
+pub(crate) mod task;
 
-use self::{handle::JoinHandle, task::Task};
-use crate::{reactor::Reactor, tp::ThreadPool};
+mod blocking;
+
+pub(crate) use self::task::Header;
+
+use self::{
+    blocking::BlockingTask,
+    handle::{JoinError, JoinHandle},
+    task::Task,
+};
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+use crate::uring::Uring;
+use crate::{
+    reactor::{driver::Driver, Reactor},
+    tp::ThreadPool,
+};
 use futures::channel::oneshot;
 use parking_lot::Mutex;
 use std::{
+    any::Any,
     future::Future,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
+    io, panic,
+    sync::{mpsc, Arc},
 };
 
 /// Asynchronous runtime on top of the thread pool
@@ -21,7 +33,24 @@ pub struct AsyncRuntime(Arc<Inner>);
 
 struct Inner {
     thread_pool: ThreadPool,
+    /// Pool for the operations which block the thread, so they don't stall the async tasks
+    blocking_pool: ThreadPool,
     reactor: Reactor,
+    driver: Mutex<Driver>,
+    /// `None` if io_uring is disabled or not supported by the kernel
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    uring: Option<Uring>,
+    /// Notifications for `block_on`, taken by its call
+    done_tx: mpsc::Sender<Done>,
+    done_rx: Mutex<Option<mpsc::Receiver<Done>>>,
+}
+
+/// Reason for `block_on` to return
+enum Done {
+    /// Main task is completed, with the panic payload if it panicked
+    Main(Option<Box<dyn Any + Send>>),
+    /// Reactor failed, so no I/O can make progress anymore
+    ReactorFailed(io::Error),
 }
 
 impl AsyncRuntime {
@@ -29,52 +58,94 @@ impl AsyncRuntime {
         &self.0.reactor
     }
 
-    pub(crate) fn new(thread_count: usize) -> Self {
+    pub(crate) fn new(
+        thread_count: usize,
+        blocking_thread_count: usize,
+        #[cfg(all(feature = "io-uring", target_os = "linux"))] io_uring: bool,
+    ) -> Self {
         assert!(thread_count != 0);
+        assert!(blocking_thread_count != 0);
 
         let thread_pool = ThreadPool::new(thread_count);
+        let blocking_pool = ThreadPool::new(blocking_thread_count);
         let reactor = Reactor::new();
+        let (done_tx, done_rx) = mpsc::channel();
+        let driver = Driver::spawn(reactor.clone(), {
+            let done_tx = done_tx.clone();
+            move |e| {
+                done_tx.send(Done::ReactorFailed(e)).ok();
+            }
+        });
 
         Self(Arc::new(Inner {
             thread_pool,
+            blocking_pool,
             reactor,
+            driver: Mutex::new(driver),
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            uring: io_uring
+                .then(|| Uring::new(crate::uring::ENTRIES).ok())
+                .flatten(),
+            done_tx,
+            done_rx: Mutex::new(Some(done_rx)),
         }))
     }
 
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    pub(crate) fn uring(&self) -> Option<&Uring> {
+        self.0.uring.as_ref()
+    }
+
     /// Block current thread until main task completion
     pub(crate) fn block_on<F>(&self, fut: F)
     where
         F: Future<Output = ()> + Send + 'static,
     {
-        let completed = Arc::new(AtomicBool::new(false));
-
-        // When the main task becomes `Ready`, than we set complete flag as true
-        let ready_fn = {
-            let completed = Arc::clone(&completed);
-            move |_| {
-                completed.store(true, Ordering::Release);
-            }
+        let done_rx = self
+            .0
+            .done_rx
+            .lock()
+            .take()
+            .expect("runtime is already blocked on");
+
+        // When the main task becomes `Ready`, the caller thread is notified with its panic payload
+        let done_tx = self.0.done_tx.clone();
+        let ready_fn = move |res: Result<(), JoinError>| {
+            done_tx
+                .send(Done::Main(res.err().and_then(|e| e.try_into_panic().ok())))
+                .ok();
         };
 
-        self.spawn(fut, ready_fn);
+        self.spawn(fut, Header::new(None, Default::default()).into(), ready_fn);
 
-        loop {
-            if completed.load(Ordering::Acquire) {
-                break;
-            } else {
-                //  While the main thread has nothing to do, let’s give it to read reactor events
-                self.0.reactor.poll_events().unwrap();
-            }
-        }
+        // I/O events are dispatched by the driver thread, so there is nothing to do meanwhile.
+        // Unless it fails, as the main task would never complete then.
+        let done = done_rx.recv().expect("main task result channel closed");
 
         self.0
             .thread_pool
             .join()
             .expect("runtime thread pool join error");
+        self.0
+            .blocking_pool
+            .join()
+            .expect("runtime blocking pool join error");
+        self.0.driver.lock().shutdown();
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        if let Some(ref uring) = self.0.uring {
+            uring.shutdown();
+        }
+
+        match done {
+            // Main task panic is propagated to the thread which called `block_on`
+            Done::Main(Some(payload)) => panic::resume_unwind(payload),
+            Done::Main(None) => {}
+            Done::ReactorFailed(e) => panic!("reactor failed: {e}"),
+        }
     }
 
     /// Create new async task
-    pub(crate) fn spawn_task<T, F>(&self, fut: F) -> JoinHandle<T>
+    pub(crate) fn spawn_task<T, F>(&self, fut: F, header: Header) -> JoinHandle<T>
     where
         T: Send + 'static,
         F: Future<Output = T> + Send + 'static,
@@ -93,22 +164,53 @@ impl AsyncRuntime {
                 .ok();
         };
 
-        self.spawn(fut, ready_fn);
+        let header = Arc::new(header);
+        let task = self.spawn(fut, Arc::clone(&header), ready_fn);
 
-        JoinHandle::new(res_rx)
+        JoinHandle::new(res_rx, &task, &header)
     }
 
-    fn spawn<T, F>(&self, fut: F, ready_fn: impl Fn(T) + Send + Sync + 'static)
+    /// Run the closure on the blocking pool
+    pub(crate) fn spawn_blocking<T, F>(&self, f: F) -> JoinHandle<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (res_tx, res_rx) = oneshot::channel();
+
+        let header = Arc::new(Header::new(None, Default::default()));
+        let task = Arc::new(BlockingTask::default());
+
+        self.0.blocking_pool.spawn({
+            let header = Arc::clone(&header);
+            let task = Arc::clone(&task);
+            move || {
+                // `JoinHandle` may be dropped meanwhile, ignore the send error
+                res_tx.send(task.run(&header, f)).ok();
+            }
+        });
+
+        JoinHandle::new(res_rx, &task, &header)
+    }
+
+    fn spawn<T, F>(
+        &self,
+        fut: F,
+        header: Arc<Header>,
+        ready_fn: impl Fn(Result<T, JoinError>) + Send + Sync + 'static,
+    ) -> Arc<Task<T>>
     where
         T: Send + 'static,
         F: Future<Output = T> + Send + 'static,
     {
         let fut = Box::pin(fut);
 
-        let task = Task::new(fut, self.clone(), ready_fn).into();
+        let task = Arc::new(Task::new(header, fut, self.clone(), ready_fn));
 
         // Immediately ask the task to begin execution
-        self.schedule_task(task);
+        self.schedule_task(Arc::clone(&task));
+
+        task
     }
 
     /// Schedule task for polling
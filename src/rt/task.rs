@@ -1,18 +1,77 @@
-use super::AsyncRuntime;
-use parking_lot::Mutex;
+use super::{handle::JoinError, AsyncRuntime};
+use crate::task::{coop, Id};
+use parking_lot::{Mutex, MutexGuard};
 use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
     future::Future,
+    panic::{self, AssertUnwindSafe},
     pin::Pin,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     task::{Context, Poll, Wake},
 };
 
 type TaskFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
 
+/// Task-local values indexed by the `LocalKey` address
+pub(crate) type Locals = HashMap<usize, Arc<dyn Any + Send + Sync>>;
+
+thread_local! {
+    /// Header of the task which is polled on the current thread
+    static CURRENT: RefCell<Option<Arc<Header>>> = const { RefCell::new(None) };
+}
+
+/// Header of the task which is currently polled on this thread
+pub(crate) fn current() -> Option<Arc<Header>> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Type-erased task properties
+pub(crate) struct Header {
+    id: Id,
+    name: Option<Arc<str>>,
+    locals: Mutex<Locals>,
+}
+
+impl Header {
+    pub(crate) fn new(name: Option<Arc<str>>, locals: Locals) -> Self {
+        Self {
+            id: Id::next(),
+            name,
+            locals: Mutex::new(locals),
+        }
+    }
+
+    pub(crate) fn id(&self) -> Id {
+        self.id
+    }
+
+    pub(crate) fn name(&self) -> Option<&Arc<str>> {
+        self.name.as_ref()
+    }
+
+    pub(crate) fn locals(&self) -> MutexGuard<'_, Locals> {
+        self.locals.lock()
+    }
+}
+
+/// Type-erased task cancellation
+pub(crate) trait Cancel: Send + Sync {
+    /// Ask the task to drop its future. The task completes with the cancellation error on the
+    /// next poll.
+    fn cancel(self: Arc<Self>);
+}
+
 pub(crate) struct Task<T> {
+    header: Arc<Header>,
     fut: Mutex<Option<TaskFuture<T>>>,
+    cancelled: AtomicBool,
     rt: AsyncRuntime,
-    ready_fn: Box<dyn Fn(T) + Send + Sync + 'static>,
+    ready_fn: Box<dyn Fn(Result<T, JoinError>) + Send + Sync + 'static>,
 }
 
 impl<T> Task<T>
@@ -20,12 +79,15 @@ where
     T: Send + 'static,
 {
     pub(crate) fn new(
+        header: Arc<Header>,
         fut: TaskFuture<T>,
         rt: AsyncRuntime,
-        ready_fn: impl Fn(T) + Send + Sync + 'static,
+        ready_fn: impl Fn(Result<T, JoinError>) + Send + Sync + 'static,
     ) -> Self {
         Self {
+            header,
             fut: Mutex::new(Some(fut)),
+            cancelled: AtomicBool::new(false),
             rt,
             ready_fn: Box::new(ready_fn),
         }
@@ -34,11 +96,26 @@ where
     pub(crate) fn poll(self: Arc<Self>) {
         let mut lock = self.fut.lock();
         if let Some(mut fut) = lock.take() {
+            if self.cancelled.load(Ordering::Acquire) {
+                drop(fut);
+                (self.ready_fn)(Err(JoinError::cancelled(&self.header)));
+                return;
+            }
+
             let waker = Arc::clone(&self).into();
             let mut cx = Context::from_waker(&waker);
-            match fut.as_mut().poll(&mut cx) {
-                Poll::Ready(res) => (self.ready_fn)(res),
-                Poll::Pending => *lock = Some(fut),
+
+            let _enter = EnterGuard::new(Arc::clone(&self.header));
+
+            // Panic must not kill the worker thread: catch it and pass to the `JoinHandle`
+            let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                coop::budget(|| fut.as_mut().poll(&mut cx))
+            }));
+
+            match res {
+                Ok(Poll::Ready(res)) => (self.ready_fn)(Ok(res)),
+                Ok(Poll::Pending) => *lock = Some(fut),
+                Err(payload) => (self.ready_fn)(Err(JoinError::panic(&self.header, payload))),
             };
         };
     }
@@ -52,3 +129,28 @@ where
         self.rt.schedule_task(Arc::clone(&self))
     }
 }
+
+impl<T> Cancel for Task<T>
+where
+    T: Send + 'static,
+{
+    fn cancel(self: Arc<Self>) {
+        self.cancelled.store(true, Ordering::Release);
+        self.rt.schedule_task(Arc::clone(&self))
+    }
+}
+
+/// Makes the task current for the thread while it's polled
+struct EnterGuard(Option<Arc<Header>>);
+
+impl EnterGuard {
+    fn new(header: Arc<Header>) -> Self {
+        Self(CURRENT.with(|current| current.replace(Some(header))))
+    }
+}
+
+impl Drop for EnterGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|current| *current.borrow_mut() = self.0.take());
+    }
+}
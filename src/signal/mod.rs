@@ -0,0 +1,17 @@
+//! Asynchronous signal handling
+
+pub mod unix;
+
+#[cfg(test)]
+mod tests;
+
+use std::io;
+
+/// Wait for `SIGINT`, which is sent on Ctrl-C.
+///
+/// The handler is installed on the first call and stays for the rest of the process lifetime,
+/// so the default behaviour of terminating the process is disabled.
+pub async fn ctrl_c() -> io::Result<()> {
+    unix::signal(unix::SignalKind::interrupt())?.recv().await;
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::signal::unix::{signal, SignalKind};
+use futures::executor::block_on;
+
+#[test]
+fn test_signal() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let mut first = signal(SignalKind::user_defined1()).unwrap();
+        let mut second = signal(SignalKind::user_defined1()).unwrap();
+
+        // SAFETY: the call has no memory safety preconditions
+        unsafe { libc::kill(libc::getpid(), libc::SIGUSR1) };
+
+        first.recv().await.unwrap();
+        second.recv().await.unwrap();
+
+        signal(SignalKind::from_raw(libc::SIGKILL)).is_err()
+    });
+
+    assert!(block_on(handle).unwrap());
+}
+
+#[test]
+fn test_signal_tasks() {
+    crate::test_runtime();
+
+    let listeners: Vec<_> = (0..2)
+        .map(|_| {
+            let mut signal = signal(SignalKind::user_defined2()).unwrap();
+            crate::spawn(async move { signal.recv().await })
+        })
+        .collect();
+
+    // SAFETY: the call has no memory safety preconditions
+    unsafe { libc::kill(libc::getpid(), libc::SIGUSR2) };
+
+    for listener in listeners {
+        assert!(block_on(listener).unwrap().is_some());
+    }
+}
@@ -0,0 +1,304 @@
+use crate::reactor::{io_handle::IoHandle, scheduled_io::Direction};
+use futures::Stream;
+use mio::net::UnixStream;
+use parking_lot::Mutex;
+use std::{
+    future::poll_fn,
+    io::{self, ErrorKind, Read},
+    mem,
+    os::fd::AsRawFd,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        OnceLock,
+    },
+    task::{Context, Poll, Waker},
+};
+
+#[cfg(any(target_os = "android", target_os = "netbsd", target_os = "openbsd"))]
+use libc::__errno as errno_location;
+#[cfg(target_os = "linux")]
+use libc::__errno_location as errno_location;
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+))]
+use libc::__error as errno_location;
+
+/// Kind of the signal which can be listened to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SignalKind(libc::c_int);
+
+impl SignalKind {
+    /// Signal with the raw number, e.g. the real-time one
+    pub const fn from_raw(signum: libc::c_int) -> Self {
+        Self(signum)
+    }
+
+    pub const fn as_raw_value(&self) -> libc::c_int {
+        self.0
+    }
+
+    /// `SIGALRM`
+    pub const fn alarm() -> Self {
+        Self(libc::SIGALRM)
+    }
+
+    /// `SIGCHLD`
+    pub const fn child() -> Self {
+        Self(libc::SIGCHLD)
+    }
+
+    /// `SIGHUP`
+    pub const fn hangup() -> Self {
+        Self(libc::SIGHUP)
+    }
+
+    /// `SIGINT`
+    pub const fn interrupt() -> Self {
+        Self(libc::SIGINT)
+    }
+
+    /// `SIGPIPE`
+    pub const fn pipe() -> Self {
+        Self(libc::SIGPIPE)
+    }
+
+    /// `SIGQUIT`
+    pub const fn quit() -> Self {
+        Self(libc::SIGQUIT)
+    }
+
+    /// `SIGTERM`
+    pub const fn terminate() -> Self {
+        Self(libc::SIGTERM)
+    }
+
+    /// `SIGUSR1`
+    pub const fn user_defined1() -> Self {
+        Self(libc::SIGUSR1)
+    }
+
+    /// `SIGUSR2`
+    pub const fn user_defined2() -> Self {
+        Self(libc::SIGUSR2)
+    }
+
+    /// `SIGWINCH`
+    pub const fn window_change() -> Self {
+        Self(libc::SIGWINCH)
+    }
+}
+
+/// Stream of the signal deliveries.
+///
+/// The deliveries happened between two receives are coalesced into one.
+#[derive(Debug)]
+pub struct Signal {
+    signum: usize,
+    /// Number of the deliveries already received
+    seen: u64,
+}
+
+/// Listen to the signal. The handler is installed on the first call for the signal and stays for
+/// the rest of the process lifetime.
+pub fn signal(kind: SignalKind) -> io::Result<Signal> {
+    let signum = kind.0;
+    if signum < 0 || FORBIDDEN.contains(&signum) {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("signal {} can't be handled", signum),
+        ));
+    }
+
+    let globals = globals()?;
+    let info = globals
+        .signals
+        .get(signum as usize)
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "signal number is out of range"))?;
+
+    info.installed
+        .get_or_init(|| install(signum).map_err(|e| e.raw_os_error().unwrap_or(libc::EINVAL)))
+        .map_err(io::Error::from_raw_os_error)?;
+
+    Ok(Signal {
+        signum: signum as usize,
+        seen: info.deliveries.load(Ordering::Acquire),
+    })
+}
+
+impl Signal {
+    /// Wait for the next delivery of the signal. Never returns `None` unless the runtime's
+    /// signal pipe fails.
+    pub async fn recv(&mut self) -> Option<()> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        let Some(globals) = GLOBALS.get() else {
+            return Poll::Ready(None);
+        };
+        let info = &globals.signals[self.signum];
+
+        loop {
+            // The waker is registered before the counter is checked, so the broadcast made by
+            // another listener after the check wakes this one up
+            info.register(cx.waker());
+
+            let deliveries = info.deliveries.load(Ordering::Acquire);
+            if deliveries != self.seen {
+                self.seen = deliveries;
+                return Poll::Ready(Some(()));
+            }
+
+            // Any listener drains the pipe for all of them and wakes up the listeners of the
+            // delivered signals
+            let mut buf = [0; 128];
+            match globals
+                .receiver
+                .poll_io(cx, Direction::Read, |mut receiver| receiver.read(&mut buf))
+            {
+                Poll::Ready(Ok(_)) => globals.broadcast(),
+                Poll::Ready(Err(_)) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl Stream for Signal {
+    type Item = ();
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+/// Signals whose handling is either impossible or unsound
+const FORBIDDEN: [libc::c_int; 5] = [
+    libc::SIGILL,
+    libc::SIGFPE,
+    libc::SIGKILL,
+    libc::SIGSEGV,
+    libc::SIGSTOP,
+];
+
+/// Self-pipe shared by all of the signals: the handler marks the signal as pending and writes
+/// to the pipe, which is registered in the reactor.
+struct Globals {
+    sender: UnixStream,
+    receiver: IoHandle<UnixStream>,
+    signals: Box<[SignalInfo]>,
+}
+
+#[derive(Default)]
+struct SignalInfo {
+    installed: OnceLock<Result<(), i32>>,
+    pending: AtomicBool,
+    deliveries: AtomicU64,
+    /// Listeners waiting for the next delivery
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl SignalInfo {
+    fn register(&self, waker: &Waker) {
+        let mut wakers = self.wakers.lock();
+        if !wakers.iter().any(|w| w.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+}
+
+static GLOBALS: OnceLock<Globals> = OnceLock::new();
+
+fn globals() -> io::Result<&'static Globals> {
+    if let Some(globals) = GLOBALS.get() {
+        return Ok(globals);
+    }
+
+    let (sender, receiver) = UnixStream::pair()?;
+    let globals = Globals {
+        sender,
+        receiver: IoHandle::with_interest(receiver, mio::Interest::READABLE)?,
+        signals: (0..=max_signum()).map(|_| SignalInfo::default()).collect(),
+    };
+
+    // Another thread may have initialized the globals meanwhile, then ours are dropped
+    Ok(GLOBALS.get_or_init(|| globals))
+}
+
+impl Globals {
+    fn broadcast(&self) {
+        for info in self.signals.iter() {
+            if info.pending.swap(false, Ordering::AcqRel) {
+                info.deliveries.fetch_add(1, Ordering::AcqRel);
+
+                let wakers = mem::take(&mut *info.wakers.lock());
+                wakers.into_iter().for_each(Waker::wake);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn max_signum() -> libc::c_int {
+    libc::SIGRTMAX()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn max_signum() -> libc::c_int {
+    33
+}
+
+fn install(signum: libc::c_int) -> io::Result<()> {
+    // SAFETY: all-zero is the valid `sigaction`, the mask is initialized right after
+    let mut action = unsafe { mem::zeroed::<libc::sigaction>() };
+    action.sa_sigaction = handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    action.sa_flags = libc::SA_RESTART;
+    // SAFETY: the mask is valid for writing
+    unsafe { libc::sigemptyset(&mut action.sa_mask) };
+
+    // SAFETY: the handler only performs the async-signal-safe operations
+    if unsafe { libc::sigaction(signum, &action, std::ptr::null_mut()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Signal handler. Only atomics and `write` are used, as they are async-signal-safe.
+extern "C" fn handler(signum: libc::c_int) {
+    // `write` may change `errno`, which the interrupted code is about to read
+    let _errno = ErrnoGuard::save();
+
+    let Some(globals) = GLOBALS.get() else {
+        return;
+    };
+
+    if let Some(info) = globals.signals.get(signum as usize) {
+        info.pending.store(true, Ordering::Release);
+    }
+
+    // The pipe may be full, then the wakeup is already pending anyway
+    // SAFETY: the byte is valid for reading
+    unsafe { libc::write(globals.sender.as_raw_fd(), [1u8].as_ptr().cast(), 1) };
+}
+
+/// Restores `errno` on drop
+struct ErrnoGuard(libc::c_int);
+
+impl ErrnoGuard {
+    fn save() -> Self {
+        // SAFETY: the location of the thread's `errno` is always valid
+        Self(unsafe { *errno_location() })
+    }
+}
+
+impl Drop for ErrnoGuard {
+    fn drop(&mut self) {
+        // SAFETY: the location of the thread's `errno` is always valid
+        unsafe { *errno_location() = self.0 };
+    }
+}
@@ -0,0 +1,12 @@
+use crate::rt::handle::JoinHandle;
+
+/// Run the blocking closure on the dedicated thread pool, so it doesn't stall the async tasks.
+///
+/// Aborting the handle has no effect once the closure has started.
+pub fn spawn_blocking<T, F>(f: F) -> JoinHandle<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    crate::runtime().spawn_blocking(f)
+}
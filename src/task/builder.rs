@@ -0,0 +1,45 @@
+use crate::{
+    rt::task::{self, Header},
+    runtime, JoinHandle,
+};
+use std::future::Future;
+
+/// Task factory, which can be used to configure the properties of a new task
+#[derive(Default)]
+pub struct Builder {
+    name: Option<String>,
+    inherit_locals: bool,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Assign a name to the task. It's shown in the `JoinError` messages and `JoinHandle`
+    /// debug output.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Copy the task-local values of the spawning task into the new task. Disabled by default.
+    pub fn inherit_locals(mut self, val: bool) -> Self {
+        self.inherit_locals = val;
+        self
+    }
+
+    pub fn spawn<T, F>(self, fut: F) -> JoinHandle<T>
+    where
+        T: Send + 'static,
+        F: Future<Output = T> + Send + 'static,
+    {
+        let locals = match task::current() {
+            Some(parent) if self.inherit_locals => parent.locals().clone(),
+            _ => Default::default(),
+        };
+
+        let header = Header::new(self.name.map(Into::into), locals);
+        runtime().spawn_task(fut, header)
+    }
+}
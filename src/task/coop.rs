@@ -0,0 +1,82 @@
+use std::{
+    cell::Cell,
+    future::poll_fn,
+    task::{ready, Context, Poll},
+};
+
+/// Amount of operations a task may perform during a single poll before it's forced to yield
+const INITIAL_BUDGET: u8 = 128;
+
+thread_local! {
+    /// Remaining budget of the task which is polled on the current thread. `None` means
+    /// that we are outside of a task and the budget is unconstrained.
+    static BUDGET: Cell<Option<u8>> = const { Cell::new(None) };
+}
+
+/// Run the closure with a fresh budget. The previous budget is restored afterwards.
+pub(crate) fn budget<R>(f: impl FnOnce() -> R) -> R {
+    let prev = BUDGET.with(|b| b.replace(Some(INITIAL_BUDGET)));
+    let _reset = ResetGuard(prev);
+    f()
+}
+
+/// Consume one unit of the task budget.
+///
+/// If the budget is exhausted, the task is woken up immediately and `Poll::Pending` is returned,
+/// so the task goes back to the end of the queue and other tasks get a chance to run.
+///
+/// The unit is given back when the returned guard is dropped, unless the operation reports its
+/// progress with `RestoreOnPending::made_progress`.
+pub(crate) fn poll_proceed(cx: &mut Context<'_>) -> Poll<RestoreOnPending> {
+    BUDGET.with(|b| match b.get() {
+        Some(0) => {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+        Some(n) => {
+            b.set(Some(n - 1));
+            Poll::Ready(RestoreOnPending(Cell::new(Some(n))))
+        }
+        None => Poll::Ready(RestoreOnPending(Cell::new(None))),
+    })
+}
+
+/// Budget unit consumed by the operation, which is given back on drop if the operation didn't
+/// make progress
+#[must_use]
+pub(crate) struct RestoreOnPending(Cell<Option<u8>>);
+
+impl RestoreOnPending {
+    /// Keep the unit consumed
+    pub(crate) fn made_progress(&self) {
+        self.0.set(None);
+    }
+}
+
+impl Drop for RestoreOnPending {
+    fn drop(&mut self) {
+        if let Some(budget) = self.0.get() {
+            BUDGET.with(|b| b.set(Some(budget)));
+        }
+    }
+}
+
+/// Consume one unit of the task budget, yielding to the scheduler if it's exhausted.
+///
+/// Use it in loops which never touch asynk I/O (e.g. CPU-bound work or always ready
+/// third-party futures) to keep them from monopolising the worker thread.
+pub async fn consume_budget() {
+    poll_fn(|cx| {
+        ready!(poll_proceed(cx)).made_progress();
+        Poll::Ready(())
+    })
+    .await
+}
+
+struct ResetGuard(Option<u8>);
+
+impl Drop for ResetGuard {
+    fn drop(&mut self) {
+        BUDGET.with(|b| b.set(self.0));
+    }
+}
@@ -0,0 +1,39 @@
+use crate::rt::task;
+use std::{
+    fmt,
+    num::NonZeroU64,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Unique identifier of the task
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id(NonZeroU64);
+
+impl Id {
+    pub(crate) fn next() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        Self(NonZeroU64::new(id).expect("task id counter overflow"))
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Identifier of the currently running task.
+///
+/// # Panics
+///
+/// Panics if called outside of a task.
+pub fn id() -> Id {
+    try_id().expect("`task::id` called outside of a task")
+}
+
+/// Identifier of the currently running task, or `None` if called outside of a task
+pub fn try_id() -> Option<Id> {
+    task::current().map(|header| header.id())
+}
@@ -0,0 +1,87 @@
+use crate::{
+    rt::handle::{AbortHandle, JoinError, JoinHandle},
+    spawn,
+};
+use futures::{stream::FuturesUnordered, StreamExt};
+use std::{fmt, future::Future};
+
+/// Collection of tasks spawned on the runtime.
+///
+/// Results are returned in the order of tasks completion. All tasks which are still running
+/// are aborted when the set is dropped.
+pub struct JoinSet<T> {
+    tasks: FuturesUnordered<JoinHandle<T>>,
+}
+
+impl<T> JoinSet<T> {
+    pub fn new() -> Self {
+        Self {
+            tasks: FuturesUnordered::new(),
+        }
+    }
+
+    /// Number of tasks in the set, including the completed ones which results are not taken yet
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Abort all tasks in the set. Their results are still returned by `join_next`.
+    pub fn abort_all(&mut self) {
+        self.tasks.iter().for_each(JoinHandle::abort);
+    }
+}
+
+impl<T> JoinSet<T>
+where
+    T: Send + 'static,
+{
+    /// Spawn the task and add it to the set
+    pub fn spawn<F>(&mut self, fut: F) -> AbortHandle
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        self.insert(spawn(fut))
+    }
+
+    /// Add already spawned task to the set, e.g. the one created with `task::Builder`
+    pub fn insert(&mut self, handle: JoinHandle<T>) -> AbortHandle {
+        let abort = handle.abort_handle();
+        self.tasks.push(handle);
+        abort
+    }
+
+    /// Wait for any task in the set to complete and take its result.
+    ///
+    /// Returns `None` if the set is empty.
+    pub async fn join_next(&mut self) -> Option<Result<T, JoinError>> {
+        self.tasks.next().await
+    }
+
+    /// Abort all tasks and wait for them to finish
+    pub async fn shutdown(&mut self) {
+        self.abort_all();
+        while self.join_next().await.is_some() {}
+    }
+}
+
+impl<T> Default for JoinSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for JoinSet<T> {
+    fn drop(&mut self) {
+        self.abort_all();
+    }
+}
+
+impl<T> fmt::Debug for JoinSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinSet").field("len", &self.len()).finish()
+    }
+}
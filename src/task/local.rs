@@ -0,0 +1,170 @@
+use crate::rt::task::{self, Header};
+use std::{
+    any::Any,
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// Declare new task-local keys of type `asynk::task::LocalKey`
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty; $($rest:tt)*) => {
+        $crate::task_local!($(#[$attr])* $vis static $name: $t);
+        $crate::task_local!($($rest)*);
+    };
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty) => {
+        $(#[$attr])*
+        $vis static $name: $crate::task::LocalKey<$t> = $crate::task::LocalKey::new();
+    };
+}
+
+/// Key for the value stored in the task record. The value follows the task across await points
+/// on any worker thread.
+///
+/// Created with the `task_local!` macro.
+pub struct LocalKey<T> {
+    // Keys are told apart by the address, so the key must not be zero-sized
+    _anchor: u8,
+    _marker: PhantomData<fn() -> T>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("task-local value is not set")]
+pub struct AccessError;
+
+impl<T> LocalKey<T>
+where
+    T: Send + Sync + 'static,
+{
+    #[doc(hidden)]
+    pub const fn new() -> Self {
+        Self {
+            _anchor: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the value of the key for the duration of the future's polls
+    pub fn scope<F>(&'static self, value: T, fut: F) -> TaskLocalFuture<T, F>
+    where
+        F: Future,
+    {
+        TaskLocalFuture {
+            key: self,
+            value: Some(Arc::new(value)),
+            fut,
+        }
+    }
+
+    /// Access the value of the key in the current task.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of the task or the value is not set by `LocalKey::scope`.
+    pub fn with<R>(&'static self, f: impl FnOnce(&T) -> R) -> R {
+        self.try_with(f).expect("cannot access task-local value")
+    }
+
+    /// Access the value of the key in the current task, or return the `AccessError` if
+    /// it's not set
+    pub fn try_with<R>(&'static self, f: impl FnOnce(&T) -> R) -> Result<R, AccessError> {
+        let value = task::current()
+            .and_then(|header| header.locals().get(&self.addr()).cloned())
+            .ok_or(AccessError)?;
+
+        let value = value
+            .downcast_ref::<T>()
+            .expect("task-local value type mismatch");
+
+        Ok(f(value))
+    }
+
+    fn addr(&'static self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+impl<T> fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKey").finish_non_exhaustive()
+    }
+}
+
+/// Future which sets the task-local value while it's polled
+pub struct TaskLocalFuture<T, F>
+where
+    T: 'static,
+{
+    key: &'static LocalKey<T>,
+    value: Option<Arc<dyn Any + Send + Sync>>,
+    fut: F,
+}
+
+impl<T, F> Future for TaskLocalFuture<T, F>
+where
+    T: Send + Sync + 'static,
+    F: Future,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `fut` is never moved out of the pinned structure
+        let this = unsafe { self.get_unchecked_mut() };
+        let fut = unsafe { Pin::new_unchecked(&mut this.fut) };
+
+        let Some(header) = task::current() else {
+            // Polled outside of a task, so there is no place to store the value
+            return fut.poll(cx);
+        };
+
+        let _scope = ScopeGuard::enter(&header, this.key.addr(), &mut this.value);
+
+        fut.poll(cx)
+    }
+}
+
+/// Puts the value into the task record and takes it back on drop, restoring the value
+/// of the outer scope
+struct ScopeGuard<'a> {
+    header: &'a Header,
+    key: usize,
+    value: &'a mut Option<Arc<dyn Any + Send + Sync>>,
+    prev: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+impl<'a> ScopeGuard<'a> {
+    fn enter(
+        header: &'a Header,
+        key: usize,
+        value: &'a mut Option<Arc<dyn Any + Send + Sync>>,
+    ) -> Self {
+        let prev = value
+            .take()
+            .and_then(|value| header.locals().insert(key, value));
+
+        Self {
+            header,
+            key,
+            value,
+            prev,
+        }
+    }
+}
+
+impl Drop for ScopeGuard<'_> {
+    fn drop(&mut self) {
+        let mut locals = self.header.locals();
+
+        *self.value = match self.prev.take() {
+            Some(prev) => locals.insert(self.key, prev),
+            None => locals.remove(&self.key),
+        };
+    }
+}
@@ -0,0 +1,24 @@
+pub(crate) mod coop;
+
+mod blocking;
+mod builder;
+mod id;
+mod join_set;
+mod local;
+mod scope;
+mod yield_now;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::{
+    blocking::spawn_blocking,
+    builder::Builder,
+    coop::consume_budget,
+    id::{id, try_id, Id},
+    join_set::JoinSet,
+    local::{AccessError, LocalKey, TaskLocalFuture},
+    scope::{scope, Scope, ScopedJoinHandle},
+    yield_now::yield_now,
+};
+pub use crate::rt::handle::{AbortHandle, JoinError, JoinHandle};
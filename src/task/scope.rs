@@ -0,0 +1,162 @@
+use futures::{
+    channel::oneshot, future::BoxFuture, stream::FuturesUnordered, FutureExt, StreamExt,
+};
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+/// Run the body with the scope, in which the tasks are allowed to borrow the data from
+/// the outer stack frame.
+///
+/// Tasks spawned within the scope are polled by the scope future itself, concurrently with
+/// the body. The scope returns only after the body and all spawned tasks are completed. If the
+/// scope future is dropped earlier, its tasks are dropped (cancelled) along with it, so they
+/// never outlive the borrowed data.
+///
+/// The body is usually an `async move` block which takes the `Scope` by value, so bind the
+/// references to the borrowed data before the closure.
+pub async fn scope<'env, F, Fut, R>(f: F) -> R
+where
+    F: FnOnce(Scope<'env>) -> Fut,
+    Fut: Future<Output = R> + Send + 'env,
+{
+    let scope = Scope {
+        shared: Arc::new(Shared {
+            spawned: Mutex::new(Vec::new()),
+            waker: Mutex::new(None),
+        }),
+    };
+
+    let body = f(scope.clone()).boxed();
+
+    ScopeFuture {
+        body: Some(body),
+        output: None,
+        tasks: FuturesUnordered::new(),
+        shared: scope.shared,
+    }
+    .await
+}
+
+/// Handle to spawn the tasks within the scope
+pub struct Scope<'env> {
+    shared: Arc<Shared<'env>>,
+}
+
+struct Shared<'env> {
+    /// Tasks spawned since the last poll of the scope
+    spawned: Mutex<Vec<BoxFuture<'env, ()>>>,
+    /// Waker of the task which polls the scope future
+    waker: Mutex<Option<Waker>>,
+}
+
+impl<'env> Scope<'env> {
+    /// Spawn the task within the scope. The task may borrow anything that outlives the scope.
+    pub fn spawn<T, F>(&self, fut: F) -> ScopedJoinHandle<T>
+    where
+        T: Send + 'env,
+        F: Future<Output = T> + Send + 'env,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        let task = async move {
+            // `ScopedJoinHandle` may be dropped, that's okay
+            tx.send(fut.await).ok();
+        };
+
+        self.shared.spawned.lock().push(task.boxed());
+
+        // Ask the scope to pick up the new task
+        if let Some(waker) = self.shared.waker.lock().as_ref() {
+            waker.wake_by_ref();
+        }
+
+        ScopedJoinHandle(rx)
+    }
+}
+
+impl Clone for Scope<'_> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl fmt::Debug for Scope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scope").finish_non_exhaustive()
+    }
+}
+
+/// Handle to await the result of the task spawned within the scope
+pub struct ScopedJoinHandle<T>(oneshot::Receiver<T>);
+
+impl<T> Future for ScopedJoinHandle<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Tasks are dropped only together with the scope, which owns the handle as well
+        self.0
+            .poll_unpin(cx)
+            .map(|res| res.expect("scoped task dropped before completion"))
+    }
+}
+
+struct ScopeFuture<'env, R> {
+    body: Option<BoxFuture<'env, R>>,
+    output: Option<R>,
+    tasks: FuturesUnordered<BoxFuture<'env, ()>>,
+    shared: Arc<Shared<'env>>,
+}
+
+impl<R> Unpin for ScopeFuture<'_, R> {}
+
+impl<R> ScopeFuture<'_, R> {
+    /// Move the newly spawned tasks to the polled set. Returns `false` if there are none.
+    fn take_spawned(&mut self) -> bool {
+        let spawned = mem::take(&mut *self.shared.spawned.lock());
+        let any = !spawned.is_empty();
+        self.tasks.extend(spawned);
+        any
+    }
+}
+
+impl<R> Future for ScopeFuture<'_, R> {
+    type Output = R;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        *self.shared.waker.lock() = Some(cx.waker().clone());
+
+        if let Some(body) = self.body.as_mut() {
+            if let Poll::Ready(output) = body.poll_unpin(cx) {
+                self.output = Some(output);
+                self.body = None;
+            }
+        }
+
+        loop {
+            self.take_spawned();
+
+            while let Poll::Ready(Some(())) = self.tasks.poll_next_unpin(cx) {}
+
+            // Polled tasks could spawn new ones
+            if !self.take_spawned() {
+                break;
+            }
+        }
+
+        if self.body.is_none() && self.tasks.is_empty() {
+            let output = self.output.take().expect("scope polled after completion");
+            return Poll::Ready(output);
+        }
+
+        Poll::Pending
+    }
+}
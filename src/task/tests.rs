@@ -0,0 +1,185 @@
+use crate::task::{self, coop, yield_now, Builder, JoinSet};
+use futures::{channel::oneshot, executor::block_on, future, task::noop_waker_ref, FutureExt};
+use std::task::{Context, Poll};
+
+#[test]
+fn test_budget_exhaustion() {
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    coop::budget(|| {
+        let proceeded = (0..)
+            .map_while(|_| match coop::poll_proceed(&mut cx) {
+                Poll::Ready(coop) => {
+                    coop.made_progress();
+                    Some(())
+                }
+                Poll::Pending => None,
+            })
+            .count();
+
+        assert_eq!(proceeded, 128);
+        assert!(coop::poll_proceed(&mut cx).is_pending());
+    });
+
+    // Outside of the task the budget is unconstrained
+    assert!((0..1000).all(|_| coop::poll_proceed(&mut cx).is_ready()));
+}
+
+#[test]
+fn test_budget_restore() {
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    coop::budget(|| {
+        // The operations which don't make progress give their unit back
+        for _ in 0..1000 {
+            assert!(coop::poll_proceed(&mut cx).is_ready());
+        }
+    });
+}
+
+#[test]
+fn test_yield_now() {
+    let mut cx = Context::from_waker(noop_waker_ref());
+    let mut fut = Box::pin(yield_now());
+
+    assert_eq!(fut.poll_unpin(&mut cx), Poll::Pending);
+    assert_eq!(fut.poll_unpin(&mut cx), Poll::Ready(()));
+}
+
+#[test]
+fn test_task_identity() {
+    crate::test_runtime();
+
+    let handle = Builder::new().name("conn-42").spawn(async { task::id() });
+    let id = handle.id();
+
+    assert_eq!(handle.name(), Some("conn-42"));
+    assert_eq!(block_on(handle).unwrap(), id);
+    assert_ne!(block_on(crate::spawn(async { task::id() })).unwrap(), id);
+    assert_eq!(task::try_id(), None);
+}
+
+#[test]
+fn test_task_panic() {
+    crate::test_runtime();
+
+    let err = block_on(Builder::new().name("boom").spawn(async { panic!("boom") })).unwrap_err();
+
+    assert!(err.is_panic());
+    assert_eq!(err.name(), Some("boom"));
+    assert!(err.to_string().contains("(boom) panicked"));
+}
+
+#[test]
+fn test_task_dropped() {
+    crate::test_runtime();
+
+    // The future drops the waker, so nothing can ever wake the task again
+    let err = block_on(crate::spawn(future::pending::<()>())).unwrap_err();
+
+    assert!(!err.is_cancelled() && !err.is_panic());
+    assert!(err.to_string().contains("result channel dropped"));
+}
+
+crate::task_local! {
+    static TRACE_ID: u64;
+}
+
+#[test]
+fn test_task_local() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(TRACE_ID.scope(42, async {
+        let outer = TRACE_ID.with(|id| *id);
+        let nested = TRACE_ID.scope(7, async { TRACE_ID.with(|id| *id) }).await;
+        yield_now().await;
+
+        let inherited = Builder::new()
+            .inherit_locals(true)
+            .spawn(async { TRACE_ID.try_with(|id| *id).ok() });
+        let isolated = crate::spawn(async { TRACE_ID.try_with(|id| *id).ok() });
+
+        (
+            outer,
+            nested,
+            TRACE_ID.with(|id| *id),
+            inherited.await.unwrap(),
+            isolated.await.unwrap(),
+        )
+    }));
+
+    assert_eq!(block_on(handle).unwrap(), (42, 7, 42, Some(42), None));
+    assert!(TRACE_ID.try_with(|_| ()).is_err());
+}
+
+#[test]
+fn test_join_set() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let mut set = JoinSet::new();
+
+        // The task which never completes until it's aborted. The sender keeps it alive.
+        let (_tx, rx) = oneshot::channel::<()>();
+        let pending = set.spawn(rx.map(|_| 0));
+        set.spawn(async { 1 });
+
+        let first = set.join_next().await.unwrap().unwrap();
+
+        pending.abort();
+        let aborted = set.join_next().await.unwrap().unwrap_err();
+
+        (
+            first,
+            aborted.is_cancelled(),
+            set.join_next().await.is_none(),
+        )
+    });
+
+    assert_eq!(block_on(handle).unwrap(), (1, true, true));
+}
+
+#[test]
+fn test_scope() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let data = vec![1, 2, 3, 4];
+        let mut sums = [0; 2];
+
+        let (left, right) = sums.split_at_mut(1);
+        let (data_left, data_right) = data.split_at(2);
+        let data = &data;
+
+        let total = task::scope(|s| async move {
+            s.spawn(async move { left[0] = data_left.iter().sum() });
+            let right = s.spawn(async move {
+                yield_now().await;
+                right[0] = data_right.iter().sum();
+                right[0]
+            });
+
+            right.await + data.len() as i32
+        })
+        .await;
+
+        (sums, total)
+    });
+
+    assert_eq!(block_on(handle).unwrap(), ([3, 7], 11));
+}
+
+#[test]
+fn test_spawn_blocking() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let ok = task::spawn_blocking(|| std::thread::current().id()).await;
+        let panicked = task::spawn_blocking(|| panic!("boom")).await;
+        (ok.unwrap(), panicked.unwrap_err().is_panic())
+    });
+
+    let (thread_id, is_panic) = block_on(handle).unwrap();
+    assert_ne!(thread_id, std::thread::current().id());
+    assert!(is_panic);
+}
@@ -0,0 +1,327 @@
+use io_uring::{cqueue, opcode, squeue, IoUring, Probe};
+use parking_lot::Mutex;
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    io,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
+    task::{Context, Poll, Waker},
+    thread::{self, JoinHandle},
+};
+
+/// io_uring instance shared by the runtime.
+///
+/// The operations are submitted by the tasks directly, while the completions are reaped by the
+/// dedicated thread which sleeps on the eventfd signalled by the ring.
+#[derive(Clone)]
+pub(crate) struct Uring(Arc<Inner>);
+
+/// Key of the entries cancelling the operations, it's never given to an `Op`
+const CANCEL_KEY: u64 = u64::MAX;
+
+struct Inner {
+    ring: Mutex<IoUring>,
+    ops: Mutex<HashMap<u64, Lifecycle>>,
+    next_key: AtomicU64,
+    eventfd: OwnedFd,
+    send_zc: bool,
+    shutdown: AtomicBool,
+    /// Error which stopped the completions from being signalled, the submissions fail after it
+    error: OnceLock<io::Error>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+enum Lifecycle {
+    /// Submitted and awaited by the `Op`
+    Waiting(Option<Waker>),
+    /// Result is received, but the kernel still uses the buffer (zero-copy send)
+    Notify(i32, Option<Waker>),
+    Completed(i32),
+    /// `Op` was dropped, the data is kept until the kernel is done with it
+    Ignored(Box<dyn Any + Send>),
+}
+
+impl Uring {
+    /// Set up the ring. Fails if the kernel doesn't support io_uring.
+    pub fn new(entries: u32) -> io::Result<Self> {
+        let ring = IoUring::new(entries)?;
+
+        // SAFETY: the call has no memory safety preconditions
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the descriptor has just been created and is owned by nobody else
+        let eventfd = unsafe { OwnedFd::from_raw_fd(fd) };
+        ring.submitter().register_eventfd(eventfd.as_raw_fd())?;
+
+        let mut probe = Probe::new();
+        ring.submitter().register_probe(&mut probe)?;
+
+        let this = Self(Arc::new(Inner {
+            ring: Mutex::new(ring),
+            ops: Mutex::new(HashMap::new()),
+            next_key: AtomicU64::new(0),
+            eventfd,
+            send_zc: probe.is_supported(opcode::SendZc::CODE),
+            shutdown: AtomicBool::new(false),
+            error: OnceLock::new(),
+            thread: Mutex::new(None),
+        }));
+
+        let thread = thread::Builder::new().name("asynk-uring".into()).spawn({
+            let this = this.clone();
+            move || this.completion_routine()
+        })?;
+        *this.0.thread.lock() = Some(thread);
+
+        Ok(this)
+    }
+
+    /// Whether the kernel supports the zero-copy send
+    pub fn supports_send_zc(&self) -> bool {
+        self.0.send_zc
+    }
+
+    /// Submit the entry. The data referenced by the entry is owned by the returned `Op`, so it
+    /// stays alive until the operation completes, even if the `Op` is dropped.
+    pub fn submit<T>(&self, entry: squeue::Entry, data: T) -> Result<Op<T>, (io::Error, T)>
+    where
+        T: Send + 'static,
+    {
+        let key = self.0.next_key.fetch_add(1, Ordering::Relaxed);
+
+        // The ring is locked first, so the failure either sees the operation submitted or
+        // rejects it
+        let mut ring = self.0.ring.lock();
+        {
+            let mut ops = self.0.ops.lock();
+            if let Some(e) = self.0.error.get() {
+                return Err((io::Error::new(e.kind(), format!("uring failed: {e}")), data));
+            }
+            ops.insert(key, Lifecycle::Waiting(None));
+        }
+
+        let entry = entry.user_data(key);
+
+        let res = loop {
+            // SAFETY: the buffers of the entry are owned by the `Op` until the completion
+            if unsafe { ring.submission().push(&entry) }.is_ok() {
+                break ring.submit();
+            }
+
+            // Queue is full, flush it to the kernel and retry
+            if let Err(e) = ring.submit() {
+                break Err(e);
+            }
+        };
+        drop(ring);
+
+        match res {
+            Ok(_) => Ok(Op {
+                uring: self.clone(),
+                key,
+                data: Some(data),
+            }),
+            Err(e) => {
+                self.0.ops.lock().remove(&key);
+                Err((e, data))
+            }
+        }
+    }
+
+    /// Stop the completion thread and wait for it
+    pub fn shutdown(&self) {
+        let Some(thread) = self.0.thread.lock().take() else {
+            return;
+        };
+
+        self.0.shutdown.store(true, Ordering::Release);
+        self.signal();
+
+        thread.join().ok();
+    }
+
+    fn completion_routine(&self) {
+        let mut buf = [0u8; 8];
+
+        loop {
+            // SAFETY: the buffer is valid for 8 bytes, the eventfd counter size
+            let n = unsafe { libc::read(self.0.eventfd.as_raw_fd(), buf.as_mut_ptr().cast(), 8) };
+            if n < 0 {
+                let e = io::Error::last_os_error();
+                if e.kind() != io::ErrorKind::Interrupted {
+                    return self.fail(e);
+                }
+            }
+
+            if self.0.shutdown.load(Ordering::Acquire) {
+                return;
+            }
+
+            // Reap the completions first, so the ring isn't locked while the tasks are woken
+            let completions = reap(&mut self.0.ring.lock());
+            self.complete_all(completions);
+        }
+    }
+
+    /// Reject the new submissions and cancel the operations in flight. The completions can't
+    /// be signalled anymore, so they're waited for on the ring directly. The operations are
+    /// woken up only once the kernel is done with their buffers.
+    fn fail(&self, e: io::Error) {
+        let mut ring = self.0.ring.lock();
+        let keys = {
+            let ops = self.0.ops.lock();
+            self.0.error.set(e).ok();
+            ops.keys().copied().collect::<Vec<_>>()
+        };
+
+        for key in keys {
+            let entry = opcode::AsyncCancel::new(key).build().user_data(CANCEL_KEY);
+            // SAFETY: the entry references no buffers
+            while unsafe { ring.submission().push(&entry) }.is_err() {
+                if ring.submit().is_err() {
+                    return;
+                }
+            }
+        }
+        drop(ring);
+
+        loop {
+            let pending = self
+                .0
+                .ops
+                .lock()
+                .values()
+                .any(|lifecycle| !matches!(lifecycle, Lifecycle::Completed(_)));
+            if !pending || self.0.shutdown.load(Ordering::Acquire) {
+                return;
+            }
+
+            let completions = {
+                let mut ring = self.0.ring.lock();
+                match ring.submit_and_wait(1) {
+                    Err(e) if e.kind() != io::ErrorKind::Interrupted => return,
+                    _ => reap(&mut ring),
+                }
+            };
+            self.complete_all(completions);
+        }
+    }
+
+    fn complete_all(&self, completions: Vec<(u64, i32, u32)>) {
+        let mut ops = self.0.ops.lock();
+        for (key, res, flags) in completions {
+            self.complete(&mut ops, key, res, flags);
+        }
+    }
+
+    fn complete(&self, ops: &mut HashMap<u64, Lifecycle>, key: u64, res: i32, flags: u32) {
+        let Some(lifecycle) = ops.remove(&key) else {
+            return;
+        };
+
+        let (lifecycle, waker) = match lifecycle {
+            // Buffer is released, the stored result is final
+            Lifecycle::Notify(res, waker) if cqueue::notif(flags) => {
+                (Lifecycle::Completed(res), waker)
+            }
+            // More completions follow, the buffer is still in use
+            Lifecycle::Waiting(waker) if cqueue::more(flags) => {
+                (Lifecycle::Notify(res, waker), None)
+            }
+            Lifecycle::Waiting(waker) => (Lifecycle::Completed(res), waker),
+            Lifecycle::Ignored(data) if cqueue::more(flags) => (Lifecycle::Ignored(data), None),
+            Lifecycle::Ignored(_) => return,
+            lifecycle => (lifecycle, None),
+        };
+
+        ops.insert(key, lifecycle);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    fn signal(&self) {
+        let val = 1u64;
+        // SAFETY: the value is valid for 8 bytes, the eventfd counter size
+        unsafe { libc::write(self.0.eventfd.as_raw_fd(), (&val as *const u64).cast(), 8) };
+    }
+}
+
+/// Take the completions out of the ring
+fn reap(ring: &mut IoUring) -> Vec<(u64, i32, u32)> {
+    ring.completion()
+        .map(|cqe| (cqe.user_data(), cqe.result(), cqe.flags()))
+        .collect()
+}
+
+/// Submitted operation, resolving to the kernel result and the data given back
+pub(crate) struct Op<T: Send + 'static> {
+    uring: Uring,
+    key: u64,
+    // `None` only after the completion
+    data: Option<T>,
+}
+
+impl<T: Send + 'static> Unpin for Op<T> {}
+
+impl<T: Send + 'static> Future for Op<T> {
+    type Output = (io::Result<u32>, T);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut ops = self.uring.0.ops.lock();
+        let lifecycle = ops.get_mut(&self.key).expect("uring operation is missing");
+
+        match lifecycle {
+            Lifecycle::Waiting(waker) | Lifecycle::Notify(_, waker) => {
+                if !waker.as_ref().is_some_and(|w| w.will_wake(cx.waker())) {
+                    *waker = Some(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+            Lifecycle::Completed(res) => {
+                let res = *res;
+                ops.remove(&self.key);
+                drop(ops);
+
+                let res = if res < 0 {
+                    Err(io::Error::from_raw_os_error(-res))
+                } else {
+                    Ok(res as u32)
+                };
+                let data = self
+                    .data
+                    .take()
+                    .expect("uring operation is already completed");
+
+                Poll::Ready((res, data))
+            }
+            Lifecycle::Ignored(_) => unreachable!("awaited uring operation is ignored"),
+        }
+    }
+}
+
+impl<T: Send + 'static> Drop for Op<T> {
+    fn drop(&mut self) {
+        let Some(data) = self.data.take() else {
+            return;
+        };
+
+        let mut ops = self.uring.0.ops.lock();
+        match ops.get(&self.key) {
+            Some(Lifecycle::Completed(_)) | None => {
+                ops.remove(&self.key);
+            }
+            Some(_) => {
+                ops.insert(self.key, Lifecycle::Ignored(Box::new(data)));
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Completion-based operations on top of io_uring.
+//!
+//! The ring is set up when the runtime is built with `io_uring(true)`. If the kernel doesn't
+//! support io_uring, the operations fall back to the epoll reactor and the blocking pool.
+
+mod driver;
+
+#[cfg(test)]
+mod tests;
+
+pub(crate) use self::driver::Uring;
+
+use crate::{
+    net::{TcpListener, TcpStream},
+    reactor::scheduled_io::Direction,
+    task::spawn_blocking,
+};
+use io_uring::{opcode, types};
+use parking_lot::Mutex;
+use socket2::{Domain, SockAddr, Socket, Type};
+use std::{
+    io::{self, ErrorKind, Write},
+    mem,
+    net::{self, SocketAddr},
+    os::fd::{AsFd, AsRawFd, FromRawFd},
+    sync::Arc,
+};
+
+/// Number of the submission queue entries
+pub(crate) const ENTRIES: u32 = 256;
+
+/// Result of the operation on the owned buffer, which is given back regardless of the outcome
+pub type BufResult<T> = (io::Result<T>, Vec<u8>);
+
+/// Whether the operations are performed by io_uring rather than the fallback
+pub fn is_enabled() -> bool {
+    crate::runtime().uring().is_some()
+}
+
+/// Read at the offset of the file, filling the buffer up to its capacity from the start.
+/// The buffer length is set to the number of bytes read.
+pub async fn read_at(fd: &impl AsFd, mut buf: Vec<u8>, offset: u64) -> BufResult<usize> {
+    let Some(uring) = crate::runtime().uring() else {
+        let (fd, offset) = match (fd.as_fd().try_clone_to_owned(), off_t(offset)) {
+            (Ok(fd), Ok(offset)) => (fd, offset),
+            (Err(e), _) | (_, Err(e)) => return (Err(e), buf),
+        };
+        return blocking(buf, move |buf| {
+            // SAFETY: the buffer is valid for writing up to its capacity
+            let n = unsafe {
+                libc::pread(
+                    fd.as_raw_fd(),
+                    buf.as_mut_ptr().cast(),
+                    buf.capacity(),
+                    offset,
+                )
+            };
+            set_len(buf, n)
+        })
+        .await;
+    };
+
+    let entry = opcode::Read::new(
+        types::Fd(fd.as_fd().as_raw_fd()),
+        buf.as_mut_ptr(),
+        sqe_len(buf.capacity()),
+    )
+    .offset(offset)
+    .build();
+
+    let (res, mut buf) = match uring.submit(entry, buf) {
+        Ok(op) => op.await,
+        Err((e, buf)) => return (Err(e), buf),
+    };
+
+    let res = res.map(|n| {
+        // SAFETY: the kernel has initialized `n` bytes
+        unsafe { buf.set_len(n as usize) };
+        n as usize
+    });
+    (res, buf)
+}
+
+/// Write the buffer at the offset of the file
+pub async fn write_at(fd: &impl AsFd, buf: Vec<u8>, offset: u64) -> BufResult<usize> {
+    let Some(uring) = crate::runtime().uring() else {
+        let (fd, offset) = match (fd.as_fd().try_clone_to_owned(), off_t(offset)) {
+            (Ok(fd), Ok(offset)) => (fd, offset),
+            (Err(e), _) | (_, Err(e)) => return (Err(e), buf),
+        };
+        return blocking(buf, move |buf| {
+            // SAFETY: the buffer is valid for reading up to its length
+            let n = unsafe { libc::pwrite(fd.as_raw_fd(), buf.as_ptr().cast(), buf.len(), offset) };
+            cvt(n)
+        })
+        .await;
+    };
+
+    let entry = opcode::Write::new(
+        types::Fd(fd.as_fd().as_raw_fd()),
+        buf.as_ptr(),
+        sqe_len(buf.len()),
+    )
+    .offset(offset)
+    .build();
+
+    match uring.submit(entry, buf) {
+        Ok(op) => {
+            let (res, buf) = op.await;
+            (res.map(|n| n as usize), buf)
+        }
+        Err((e, buf)) => (Err(e), buf),
+    }
+}
+
+/// Accept the new connection on the listener
+pub async fn accept(listener: &TcpListener) -> io::Result<(TcpStream, SocketAddr)> {
+    let Some(uring) = crate::runtime().uring() else {
+        return listener.accept().await;
+    };
+
+    // Address storage must outlive the operation, so it's owned by the `Op`
+    // SAFETY: all-zero is the valid `sockaddr_storage`
+    let mut addr = Box::new((
+        unsafe { mem::zeroed::<libc::sockaddr_storage>() },
+        mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t,
+    ));
+    let entry = opcode::Accept::new(
+        types::Fd(listener.as_raw_fd()),
+        (&mut addr.0 as *mut libc::sockaddr_storage).cast(),
+        &mut addr.1,
+    )
+    .flags(libc::SOCK_CLOEXEC)
+    .build();
+
+    let (res, addr) = match uring.submit(entry, addr) {
+        Ok(op) => op.await,
+        Err((e, _)) => return Err(e),
+    };
+
+    match res {
+        Ok(fd) => {
+            // SAFETY: the descriptor of the accepted socket is owned by nobody else
+            let stream = unsafe { net::TcpStream::from_raw_fd(fd as i32) };
+            // SAFETY: the kernel has filled the address of the given length
+            let addr = unsafe { SockAddr::new(addr.0, addr.1) };
+            let addr = addr
+                .as_socket()
+                .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "not an inet address"))?;
+
+            Ok((from_std(stream)?, addr))
+        }
+        // The listener is non-blocking, so the ring may refuse to wait for the connection
+        Err(ref e) if e.kind() == ErrorKind::WouldBlock => listener.accept().await,
+        Err(e) => Err(e),
+    }
+}
+
+/// Open a TCP connection to the address
+pub async fn connect(addr: SocketAddr) -> io::Result<TcpStream> {
+    let Some(uring) = crate::runtime().uring() else {
+        return TcpStream::connect(addr).await;
+    };
+
+    // The socket stays blocking until connected, so the ring waits for the connection itself
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM.cloexec(), None)?;
+    let addr = Box::new(SockAddr::from(addr));
+    let entry =
+        opcode::Connect::new(types::Fd(socket.as_raw_fd()), addr.as_ptr(), addr.len()).build();
+
+    let (res, (socket, _)) = match uring.submit(entry, (socket, addr)) {
+        Ok(op) => op.await,
+        Err((e, _)) => return Err(e),
+    };
+    res?;
+
+    from_std(socket.into())
+}
+
+/// Send the buffer without copying it into the kernel. Falls back to the regular send if the
+/// kernel doesn't support the zero-copy one.
+pub async fn send_zc(stream: &TcpStream, buf: Vec<u8>) -> BufResult<usize> {
+    let uring = match crate::runtime().uring() {
+        Some(uring) if uring.supports_send_zc() => uring,
+        _ => return send(stream, buf).await,
+    };
+
+    let entry = opcode::SendZc::new(
+        types::Fd(stream.as_raw_fd()),
+        buf.as_ptr(),
+        sqe_len(buf.len()),
+    )
+    .build();
+
+    let (res, buf) = match uring.submit(entry, buf) {
+        Ok(op) => op.await,
+        Err((e, buf)) => return (Err(e), buf),
+    };
+
+    match res {
+        Ok(n) => (Ok(n as usize), buf),
+        // The stream is non-blocking, wait for the readiness with the reactor
+        Err(ref e) if e.kind() == ErrorKind::WouldBlock => send(stream, buf).await,
+        Err(e) => (Err(e), buf),
+    }
+}
+
+async fn send(stream: &TcpStream, buf: Vec<u8>) -> BufResult<usize> {
+    let res = stream
+        .io()
+        .async_io(Direction::Write, |mut stream| stream.write(&buf))
+        .await;
+    (res, buf)
+}
+
+fn from_std(stream: net::TcpStream) -> io::Result<TcpStream> {
+    stream.set_nonblocking(true)?;
+    TcpStream::new(mio::net::TcpStream::from_std(stream))
+}
+
+/// Run the operation on the buffer in the blocking pool
+async fn blocking<F>(buf: Vec<u8>, f: F) -> BufResult<usize>
+where
+    F: FnOnce(&mut Vec<u8>) -> io::Result<usize> + Send + 'static,
+{
+    // The buffer stays in the slot while the closure runs, so it's given back to the caller
+    // even if the closure panics or is never run
+    let slot = Arc::new(Mutex::new(buf));
+
+    let res = spawn_blocking({
+        let slot = Arc::clone(&slot);
+        move || f(&mut slot.lock())
+    })
+    .await;
+
+    let buf = mem::take(&mut *slot.lock());
+    (res.unwrap_or_else(|e| Err(io::Error::other(e))), buf)
+}
+
+/// Length of the buffer for the submission entry. The operation on the buffer of 4 GiB or more
+/// is partial, as it is for the syscalls limited by the kernel.
+fn sqe_len(len: usize) -> u32 {
+    u32::try_from(len).unwrap_or(u32::MAX)
+}
+
+fn off_t(offset: u64) -> io::Result<libc::off_t> {
+    libc::off_t::try_from(offset)
+        .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "offset is out of range"))
+}
+
+fn set_len(buf: &mut Vec<u8>, n: isize) -> io::Result<usize> {
+    let n = cvt(n)?;
+    // SAFETY: the syscall has initialized `n` bytes
+    unsafe { buf.set_len(n) };
+    Ok(n)
+}
+
+fn cvt(n: isize) -> io::Result<usize> {
+    if n < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(n as usize)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{net::TcpListener, uring};
+use futures::{executor::block_on, AsyncReadExt};
+use std::fs::File;
+
+#[test]
+fn test_file() {
+    crate::test_runtime();
+
+    let path = std::env::temp_dir().join(format!("asynk-uring-{}", std::process::id()));
+    let file = File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .unwrap();
+
+    let handle = crate::spawn(async move {
+        let (res, _) = uring::write_at(&file, b"hello, world".to_vec(), 0).await;
+        assert_eq!(res.unwrap(), 12);
+
+        let (res, buf) = uring::read_at(&file, Vec::with_capacity(5), 7).await;
+        assert_eq!(res.unwrap(), 5);
+        buf
+    });
+
+    let buf = block_on(handle).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(buf, b"world");
+}
+
+#[test]
+fn test_socket() {
+    crate::test_runtime();
+
+    let handle = crate::spawn(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = crate::spawn(async move {
+            let stream = uring::connect(addr).await.unwrap();
+            let (res, _) = uring::send_zc(&stream, b"ping".to_vec()).await;
+            res.unwrap()
+        });
+
+        let (mut stream, _) = uring::accept(&listener).await.unwrap();
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await.unwrap();
+
+        (client.await.unwrap(), buf)
+    });
+
+    assert_eq!(block_on(handle).unwrap(), (4, b"ping".to_vec()));
+}